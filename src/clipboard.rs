@@ -0,0 +1,41 @@
+//! Copies of secrets, cleared from the clipboard after a while
+
+use crate::config::CLIPBOARD_DAEMON_VAR;
+use arboard::Clipboard;
+use std::io::{self, Read, Write};
+use std::process::{Command, Stdio};
+use std::time::Duration;
+
+/// Copy the text, to be cleared after `timeout` seconds unless replaced by then. As the
+/// clipboard belongs to a process on some platforms (e.g. X11), a child process keeps it
+/// once the application has quit, then clears it
+pub fn copy_secret(text: &str, timeout: u64) -> io::Result<()> {
+    let mut child = Command::new(std::env::current_exe()?)
+        .env(CLIPBOARD_DAEMON_VAR, timeout.to_string())
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()?;
+    // The text goes through a pipe, not to be seen in the arguments of the process
+    child
+        .stdin
+        .take()
+        .expect("Stdin of the child process is piped")
+        .write_all(text.as_bytes())
+}
+
+/// Run as the child process of `copy_secret`: copy the text read from stdin, then clear it
+/// after the timeout if it is still in the clipboard
+pub fn run_daemon(timeout: &str) -> color_eyre::Result<()> {
+    let timeout: u64 = timeout.parse()?;
+    let mut text = String::new();
+    io::stdin().read_to_string(&mut text)?;
+
+    let mut clipboard = Clipboard::new()?;
+    clipboard.set_text(&text)?;
+    std::thread::sleep(Duration::from_secs(timeout));
+    if clipboard.get_text().is_ok_and(|current| current == text) {
+        clipboard.clear()?;
+    }
+    Ok(())
+}
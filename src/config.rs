@@ -1,4 +1,11 @@
-use ratatui::style::Color;
+//! User configuration, read from a TOML file, and the fixed settings of the application
+
+use crate::model::marks::Separator;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 
 /// Project name
 pub const PROJECT_NAME: &str = env!("CARGO_PKG_NAME");
@@ -9,28 +16,240 @@ pub const PROJECT_VERSION: &str = env!("CARGO_PKG_VERSION");
 /// Project author
 pub const PROJECT_AUTHOR: &str = env!("CARGO_PKG_AUTHORS");
 
-/// Instructions rendered in main screen
-pub const KEYBINDINGS_INFO_MAIN_SCREEN: &str =
-    " (q) quit | (↓) Go next | (↑) Go previous | (Enter) Go selected ";
-
-/// Instructions rendered in secondary screen
-pub const KEYBINDINGS_INFO_SECONDARY_SCREEN: &str =
-    " (q) quit | (Esc) Go back | (↓) Go next | (↑) Go previous | (Enter) Go selected ";
-
 /// Symbol rendered at the selected object
 pub const ITEM_SELECTION_SYMBOL_STRING: &str = ">> ";
 
-/// Color for the selected object
-pub const SELECTION_COLOR: Color = Color::LightMagenta;
+/// Symbol rendered before the groups discovered in the current project
+pub const PROJECT_GROUP_SYMBOL_STRING: &str = "⚙ ";
+
+/// Title of the virtual group with the most recently used commands
+pub const RECENT_GROUP_TITLE: &str = "Recent";
+
+/// Maximum number of commands in the "Recent" group
+pub const RECENT_GROUP_SIZE: usize = 10;
+
+/// Indentation of the child groups in the main screen, per level
+pub const TREE_INDENT_STRING: &str = "  ";
+
+/// Symbols rendered before the groups with child groups, whether shown or hidden
+pub const EXPANDED_GROUP_SYMBOL_STRING: &str = "▾ ";
+pub const COLLAPSED_GROUP_SYMBOL_STRING: &str = "▸ ";
+
+/// Separator of the group names in the title of the secondary screen
+pub const BREADCRUMB_SEPARATOR_STRING: &str = " › ";
+
+/// Symbol rendered before the pinned groups and entries
+pub const PINNED_SYMBOL_STRING: &str = "★ ";
+
+/// Badge of the commands flagged as dangerous
+pub const DANGER_SYMBOL_STRING: &str = "⚠ ";
+
+/// Badge of the commands whose program is not installed
+pub const MISSING_SYMBOL_STRING: &str = "✗ ";
+
+/// Regular expressions of the commands flagged as dangerous, unless configured otherwise
+pub const DANGEROUS_PATTERNS: [&str; 8] = [
+    r"\brm\s+(.*\s)?-\w*[rR]",
+    r"\bgit\s+push\b.*\s(--force|-f)\b",
+    r"\bgit\s+(reset\s+--hard|clean\s+-\w*f)",
+    r"\bkubectl\s+delete\b",
+    r"(?i)\bdrop\s+(table|database|schema)\b",
+    r"(?i)\btruncate\s+table\b",
+    r"\bdd\s+.*\bof=",
+    r"\bmkfs\b",
+];
+
+/// Shown in place of the secret values until they are revealed
+pub const SECRET_MASK_STRING: &str = "••••••";
+
+/// Seconds before the clipboard is cleared after copying secrets, unless configured otherwise
+pub const CLIPBOARD_TIMEOUT: u64 = 30;
+
+/// Variable of the environment telling the process to only clear the clipboard, see
+/// `clipboard_timeout`
+pub const CLIPBOARD_DAEMON_VAR: &str = "MEMORS_CLIPBOARD_DAEMON";
+
+/// Longest short description accepted by `memors lint`, in characters
+pub const LINT_SHORT_INFO_MAX: usize = 80;
+
+/// Longest long description accepted by `memors lint`, in characters
+pub const LINT_LONG_INFO_MAX: usize = 2000;
+
+/// Seconds given to `man` and `--help`, in all, to print the documentation of a program
+pub const DOCUMENTATION_TIMEOUT: u64 = 3;
+
+/// Width of the man pages read to describe the commands, in columns
+pub const DOCUMENTATION_WIDTH: u16 = 80;
+
+/// Maximum number of pinned entries in the quick-access bar, one per number key
+pub const PINNED_ENTRIES_MAX: usize = 9;
+
+/// Maximum number of changes that can be undone
+pub const HISTORY_SIZE: usize = 100;
+
+/// Maximum time between the two clicks of a double click
+pub const DOUBLE_CLICK_INTERVAL: Duration = Duration::from_millis(400);
+
+/// Lines scrolled by each step of the mouse wheel
+pub const MOUSE_SCROLL_LINES: i16 = 3;
+
+/// Title of the help popup
+pub const HELP_POPUP_TITLE: &str = "Help";
+
+/// Title of the log panel
+pub const LOG_PANEL_TITLE: &str = "Log";
+
+/// Height of the log panel, borders included
+pub const LOG_PANEL_HEIGHT: u16 = 10;
+
+/// Number of recent log messages kept for the log panel
+pub const LOG_PANEL_SIZE: usize = 200;
+
+/// Name of the directory of the log files, in the state directory
+pub const LOG_DIR_NAME: &str = "logs";
+
+/// Number of daily log files kept
+pub const LOG_FILES_MAX: usize = 7;
 
 /// Title for entry list rendered in main screen
 pub const MAIN_SCREEN_ENTRIES_TITLE: &str = "Entry list";
 
+/// Title for pinned entries rendered in main screen
+pub const MAIN_SCREEN_PINNED_TITLE: &str = "Pinned";
+
 /// Title for preview rendered in main screen
 pub const MAIN_SCREEN_PREVIEW_TITLE: &str = "Preview";
 
+/// Width of the prompt popup, borders included
+pub const PROMPT_POPUP_WIDTH: u16 = 60;
+
 /// Title for long description rendered in secondary screen
 pub const SECONDARY_SCREEN_LONG_INFO_TITLE: &str = "Description";
 
 /// Title for entry list rendered in secondary screen
 pub const SECONDARY_SCREEN_ENTRIES_TITLE: &str = "Entry list";
+
+/// Title for the selected entry rendered in zoom screen
+pub const ZOOM_SCREEN_TITLE: &str = "Entry";
+
+/// Name of the user configuration file
+pub const CONFIG_FILE_NAME: &str = "config.toml";
+
+/// User configuration, read from a TOML file. Every field is optional
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// Render long descriptions as Markdown instead of plain text
+    pub markdown: bool,
+    /// Name of the theme, either built-in or defined in `themes`
+    pub theme: String,
+    /// Custom themes, indexed by name, with the colors that differ from the dark theme.
+    /// They are read by the terminal application
+    pub themes: HashMap<String, toml::Table>,
+    /// How the marked commands are joined when copied (newline, and, semicolon)
+    pub separator: Separator,
+    /// Terminal sizes below which the layouts change
+    pub breakpoints: Breakpoints,
+    /// Regular expressions of the commands that need a confirmation to be copied
+    pub dangerous_patterns: Vec<String>,
+    /// Seconds before the clipboard is cleared when the copied commands hold secrets,
+    /// 0 to keep them
+    pub clipboard_timeout: u64,
+}
+
+/// Terminal sizes below which the screens switch to layouts that fit small terminals,
+/// e.g. a narrow tmux pane
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Breakpoints {
+    /// Below this width, the panels are stacked vertically instead of side by side
+    pub stacked_width: u16,
+    /// Below this width, only the focused panel is shown, (Tab) switching between them
+    pub single_panel_width: u16,
+    /// Below this height, the title bar is hidden and the keybindings take a single line
+    pub compact_height: u16,
+}
+
+impl Default for Breakpoints {
+    fn default() -> Self {
+        Breakpoints {
+            stacked_width: 100,
+            single_panel_width: 60,
+            compact_height: 24,
+        }
+    }
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            markdown: true,
+            theme: String::from("dark"),
+            themes: HashMap::new(),
+            separator: Separator::default(),
+            breakpoints: Breakpoints::default(),
+            dangerous_patterns: DANGEROUS_PATTERNS.map(String::from).to_vec(),
+            clipboard_timeout: CLIPBOARD_TIMEOUT,
+        }
+    }
+}
+
+impl Config {
+    /// Default location of the configuration file, e.g. ~/.config/memors/config.toml
+    pub fn default_path() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join(PROJECT_NAME).join(CONFIG_FILE_NAME))
+    }
+
+    /// Load the configuration file. A missing file gives the default configuration
+    pub fn load(path: &Path) -> io::Result<Config> {
+        match std::fs::read_to_string(path) {
+            Ok(content) => {
+                tracing::info!(path = %path.display(), "loaded the configuration");
+                toml::from_str(&content).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                tracing::debug!(path = %path.display(), "no configuration file");
+                Ok(Config::default())
+            }
+            Err(e) => Err(e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_config() {
+        let config: Config = toml::from_str("").unwrap();
+        assert_eq!(config, Config::default());
+        assert!(config.markdown);
+
+        let config: Config = toml::from_str("markdown = false").unwrap();
+        assert!(!config.markdown);
+
+        let config: Config =
+            toml::from_str("[breakpoints]\nstacked_width = 120\ncompact_height = 30").unwrap();
+        assert_eq!(config.breakpoints.stacked_width, 120);
+        assert_eq!(config.breakpoints.compact_height, 30);
+        assert_eq!(
+            config.breakpoints.single_panel_width,
+            Breakpoints::default().single_panel_width
+        );
+
+        let config: Config = toml::from_str("separator = \"semicolon\"").unwrap();
+        assert_eq!(config.separator, Separator::Semicolon);
+
+        let config: Config = toml::from_str("clipboard_timeout = 0").unwrap();
+        assert_eq!(config.clipboard_timeout, 0);
+
+        assert!(toml::from_str::<Config>("unknown = 1").is_err());
+    }
+
+    #[test]
+    fn load_missing_config() {
+        let config = Config::load(Path::new("./does/not/exist.toml")).unwrap();
+        assert_eq!(config, Config::default());
+    }
+}
@@ -0,0 +1,404 @@
+use crate::message::Message;
+use crate::model::Model;
+use crate::ui::theme::Theme;
+use crate::ui::{Hit, Rendered};
+use crate::{draw, follow_up, handle_key, handle_mouse, process};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
+use ratatui::backend::TestBackend;
+use ratatui::Terminal;
+use std::path::PathBuf;
+use std::time::Instant;
+
+/// Runs the application on a terminal of a fixed size, without any input device: the tests
+/// send it keys, clicks and messages, and look at the frames rendered in between
+pub struct Harness {
+    pub model: Model,
+    terminal: Terminal<TestBackend>,
+    theme: Theme,
+    /// Areas of the last frame, hit-tested by the clicks
+    rendered: Rendered,
+    last_click: Option<(Instant, Hit)>,
+    /// Directory of the database file, removed with the harness
+    dir: PathBuf,
+}
+
+impl Harness {
+    /// Start the application on a copy of the demo database. Every harness needs its own
+    /// `name`, as the tests run in parallel
+    pub fn new(name: &str, width: u16, height: u16) -> Harness {
+        let demo = include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/examples/demo.json"));
+        Harness::with_database(name, width, height, demo)
+    }
+
+    /// Start the application on a database of the given JSON text
+    pub fn with_database(name: &str, width: u16, height: u16, database: &str) -> Harness {
+        let dir = std::env::temp_dir().join(format!("memors-harness-{}", name));
+        std::fs::create_dir_all(&dir).unwrap();
+        let file = dir.join("demo.json");
+        std::fs::write(&file, database).unwrap();
+
+        // The programs are not looked up, for the screens not to depend on this machine
+        let mut harness = Harness {
+            model: Model {
+                search_path: None,
+                ..Model::new(file.to_string_lossy().into_owned())
+            },
+            terminal: Terminal::new(TestBackend::new(width, height)).unwrap(),
+            theme: Theme::dark(),
+            rendered: Rendered::default(),
+            last_click: None,
+            dir,
+        };
+        harness.input(Some(Message::Init));
+        harness
+    }
+
+    /// Process what an event was mapped to, if anything, then render the next frame,
+    /// like an iteration of the main loop
+    pub fn input(&mut self, msg: Option<Message>) {
+        process(&mut self.model, msg);
+        self.rendered = draw(&mut self.terminal, &mut self.model, &self.theme).unwrap();
+        while let Some(msg) = follow_up(&self.model) {
+            process(&mut self.model, Some(msg));
+            self.rendered = draw(&mut self.terminal, &mut self.model, &self.theme).unwrap();
+        }
+    }
+
+    pub fn press(&mut self, code: KeyCode) {
+        let msg = handle_key(&self.model, KeyEvent::new(code, KeyModifiers::NONE));
+        self.input(msg);
+    }
+
+    /// Press the keys of the given characters, one after the other
+    pub fn type_keys(&mut self, keys: &str) {
+        for c in keys.chars() {
+            self.press(KeyCode::Char(c));
+        }
+    }
+
+    pub fn click(&mut self, column: u16, row: u16) {
+        let mouse = MouseEvent {
+            kind: MouseEventKind::Down(MouseButton::Left),
+            column,
+            row,
+            modifiers: KeyModifiers::NONE,
+        };
+        let msg = handle_mouse(mouse, &self.rendered, &mut self.last_click);
+        self.input(msg);
+    }
+
+    /// Change the size of the terminal, and render again
+    pub fn resize(&mut self, width: u16, height: u16) {
+        self.terminal.backend_mut().resize(width, height);
+        self.input(None);
+    }
+
+    /// Write an executable script in the only directory where the programs are looked up
+    pub fn install_program(&mut self, name: &str, script: &str) {
+        let bin = self.dir.join("bin");
+        std::fs::create_dir_all(&bin).unwrap();
+        std::fs::write(bin.join(name), script).unwrap();
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let permissions = std::fs::Permissions::from_mode(0o755);
+            std::fs::set_permissions(bin.join(name), permissions).unwrap();
+        }
+        self.model.search_path = Some(bin.into_os_string());
+    }
+
+    /// Text of the last frame, one quoted line per row
+    pub fn screen(&self) -> String {
+        self.terminal.backend().to_string()
+    }
+}
+
+impl Drop for Harness {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.dir);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{CurrentScreen, RunningState};
+    use insta::assert_snapshot;
+
+    #[test]
+    fn main_screen() {
+        let mut harness = Harness::new("main_screen", 100, 30);
+        assert_snapshot!(harness.screen());
+
+        // Hide the child groups of "Git"
+        harness.press(KeyCode::Left);
+        assert_snapshot!(harness.screen());
+    }
+
+    #[test]
+    fn secondary_screen() {
+        let mut harness = Harness::new("secondary_screen", 100, 30);
+        harness.press(KeyCode::Enter);
+        assert_eq!(harness.model.current_screen, CurrentScreen::Secondary);
+        assert_snapshot!(harness.screen());
+
+        // Marked entries are numbered, and a status message replaces the keybindings
+        harness.type_keys("mjm;");
+        assert_snapshot!(harness.screen());
+    }
+
+    #[test]
+    fn zoom_screen() {
+        let mut harness = Harness::new("zoom_screen", 100, 30);
+        harness.press(KeyCode::Enter);
+        harness.type_keys("z");
+        assert_snapshot!(harness.screen());
+    }
+
+    #[test]
+    fn help_popup() {
+        let mut harness = Harness::new("help_popup", 100, 30);
+        harness.type_keys("?");
+        assert_snapshot!(harness.screen());
+
+        harness.press(KeyCode::Esc);
+        assert!(!harness.model.show_help);
+    }
+
+    #[test]
+    fn small_terminals() {
+        let mut harness = Harness::new("small_terminals", 80, 20);
+        harness.press(KeyCode::Enter);
+        assert_snapshot!(harness.screen());
+
+        // Only the focused panel is shown
+        harness.resize(50, 20);
+        assert_snapshot!(harness.screen());
+        harness.press(KeyCode::Tab);
+        assert_snapshot!(harness.screen());
+    }
+
+    #[test]
+    fn compact_layouts() {
+        let mut harness = Harness::new("compact_layouts", 100, 12);
+        harness.model.compact = true;
+        harness.press(KeyCode::Enter);
+        assert_snapshot!(harness.screen());
+    }
+
+    #[test]
+    fn click_and_copy() {
+        let mut harness = Harness::new("click_and_copy", 100, 30);
+
+        // Open "C++", listed after "Git" and its two children
+        harness.click(8, 8);
+        harness.click(8, 8);
+        assert_eq!(harness.model.current_screen, CurrentScreen::Secondary);
+        assert_eq!(harness.model.breadcrumb(), ["C++"]);
+
+        harness.type_keys("j");
+        harness.press(KeyCode::Enter);
+        assert_eq!(harness.model.running_state, RunningState::Done);
+        let command = &harness.model.entries[harness.model.idx_entrygroup].entries[1].command;
+        assert_eq!(harness.model.copied_text().as_ref(), Some(command));
+    }
+
+    #[test]
+    fn confirm_dangerous_copy() {
+        let mut harness = Harness::new("confirm_dangerous_copy", 100, 30);
+        harness.model.entries[0].entries[0].command = String::from("git push --force");
+        harness.press(KeyCode::Enter);
+
+        harness.press(KeyCode::Enter);
+        assert_eq!(harness.model.running_state, RunningState::Loaded);
+        assert_snapshot!(harness.screen());
+
+        harness.press(KeyCode::Enter);
+        assert_eq!(harness.model.running_state, RunningState::Done);
+        assert_eq!(
+            harness.model.text_to_copy().as_deref(),
+            Some("git push --force")
+        );
+    }
+
+    #[test]
+    fn store_and_copy_secret() {
+        let mut harness = Harness::new("store_and_copy_secret", 100, 30);
+        harness.press(KeyCode::Enter);
+
+        // The passphrase is asked first, then the placeholder and its value
+        harness.type_keys("S");
+        harness.type_keys("passphrase");
+        assert_snapshot!(harness.screen());
+        harness.press(KeyCode::Enter);
+        harness.press(KeyCode::Enter);
+        harness.type_keys("a.txt");
+        harness.press(KeyCode::Enter);
+        assert!(harness.model.prompt.is_none());
+
+        // Masked until revealed
+        assert_snapshot!(harness.screen());
+        harness.type_keys("r");
+        assert!(harness.screen().contains("git add a.txt"));
+
+        harness.press(KeyCode::Enter);
+        assert_eq!(harness.model.running_state, RunningState::Done);
+        assert_eq!(
+            harness.model.text_to_copy().as_deref(),
+            Some("git add a.txt")
+        );
+    }
+
+    #[test]
+    fn encrypted_database() {
+        let mut harness = Harness::new("encrypted_database", 100, 30);
+        harness.type_keys("E");
+        harness.type_keys("passphrase");
+        harness.press(KeyCode::Enter);
+        harness.type_keys("q");
+        let file = harness.model.file.clone();
+        assert!(!std::fs::read_to_string(&file).is_ok_and(|text| text.contains("git")));
+
+        // The passphrase is asked when loading, until the right one is typed
+        harness.model = Model::new(file);
+        harness.input(Some(Message::Init));
+        assert!(harness.model.entries.is_empty());
+        harness.type_keys("wrong");
+        harness.press(KeyCode::Enter);
+        assert_snapshot!(harness.screen());
+
+        harness.type_keys("passphrase");
+        harness.press(KeyCode::Enter);
+        assert!(harness.model.prompt.is_none());
+        assert_eq!(harness.model.entries[0].description, "Git");
+
+        // Without it, the application quits
+        harness.model = Model::new(harness.model.file.clone());
+        harness.input(Some(Message::Init));
+        harness.press(KeyCode::Esc);
+        assert_eq!(harness.model.running_state, RunningState::Done);
+    }
+
+    #[test]
+    fn missing_programs() {
+        let mut harness = Harness::new("missing_programs", 100, 30);
+        harness.install_program("git", "");
+        harness.model.check_programs();
+
+        // "C++" only has g++ commands, not installed
+        harness.click(8, 8);
+        harness.click(8, 8);
+        assert_snapshot!(harness.screen());
+
+        harness.type_keys("a");
+        assert_eq!(harness.model.current_screen, CurrentScreen::Main);
+        harness.press(KeyCode::Enter);
+        assert_eq!(harness.model.current_screen, CurrentScreen::Main);
+
+        // Git commands are shown
+        harness.click(8, 5);
+        harness.click(8, 5);
+        assert_eq!(harness.model.current_screen, CurrentScreen::Secondary);
+        assert!(harness.screen().contains("installed only"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn describe_from_man_page() {
+        let mut harness = Harness::new("describe_from_man_page", 100, 30);
+        let man = [
+            "GIT-COMMIT(1)",
+            "",
+            "NAME",
+            "       git-commit - Record changes to the repository",
+            "",
+            "SYNOPSIS",
+            "       git commit [-a] [-m <msg>] [--] [<pathspec>...]",
+            "",
+            "OPTIONS",
+            "       -a, --all",
+            "           Automatically stage files that have been modified and deleted.",
+            "",
+            "       -m <msg>, --message=<msg>",
+            "           Use the given <msg> as the commit message.",
+        ];
+        // Only the page of the subcommand is found
+        let script = format!(
+            "#!/bin/sh\n[ \"$1\" = git-commit ] || exit 16\nprintf '%s\\n' '{}'\n",
+            man.join("' '")
+        );
+        harness.install_program("man", &script);
+        harness.press(KeyCode::Enter);
+        harness.type_keys("j");
+
+        // Proposed for review, then kept as an undoable change
+        harness.type_keys("D");
+        assert_snapshot!(harness.screen());
+        harness.press(KeyCode::Enter);
+        let long_info = &harness.model.entries[0].entries[1].long_info;
+        assert!(long_info.starts_with("git-commit - Record changes"));
+        assert!(long_info.contains("Use the given <msg>"));
+        assert!(!long_info.contains("--all"));
+        harness.type_keys("u");
+        assert!(!harness.model.entries[0].entries[1]
+            .long_info
+            .contains("<msg>"));
+
+        // Discarded
+        harness.type_keys("D");
+        harness.press(KeyCode::Esc);
+        assert!(harness.model.prompt.is_none());
+        assert!(!harness.model.entries[0].entries[1]
+            .long_info
+            .contains("<msg>"));
+    }
+
+    #[test]
+    fn empty_database() {
+        let mut harness = Harness::with_database("empty_database", 100, 30, "[]");
+        assert!(harness.model.entries.is_empty());
+
+        // Nothing to select, pin, move, expand or open
+        harness.type_keys("jkpJK");
+        harness.press(KeyCode::Left);
+        harness.press(KeyCode::Right);
+        harness.press(KeyCode::Enter);
+        assert_eq!(harness.model.current_screen, CurrentScreen::Main);
+        assert!(harness.model.entries.is_empty());
+    }
+
+    #[test]
+    fn copy_pinned_with_marks() {
+        let mut harness = Harness::new("copy_pinned_with_marks", 100, 30);
+        harness.press(KeyCode::Enter);
+        harness.type_keys("p");
+        harness.type_keys("jmjm");
+        assert_eq!(harness.model.marked.len(), 2);
+
+        harness.type_keys("1");
+        assert_eq!(harness.model.running_state, RunningState::Done);
+        assert_eq!(
+            harness.model.text_to_copy().as_deref(),
+            Some("git add <file>")
+        );
+    }
+
+    #[test]
+    fn confirm_dangerous_pinned_copy_with_marks() {
+        let mut harness = Harness::new("confirm_dangerous_pinned_copy_with_marks", 100, 30);
+        harness.model.entries[0].entries[0].command = String::from("git push --force");
+        harness.press(KeyCode::Enter);
+        harness.type_keys("pjmjm");
+
+        // The marks are kept until the copy is confirmed
+        harness.type_keys("1");
+        assert_eq!(harness.model.running_state, RunningState::Loaded);
+        assert_eq!(harness.model.marked.len(), 2);
+        harness.type_keys("1");
+        assert_eq!(harness.model.running_state, RunningState::Done);
+        assert_eq!(
+            harness.model.text_to_copy().as_deref(),
+            Some("git push --force")
+        );
+    }
+}
@@ -0,0 +1,536 @@
+//! Key bindings of every screen. `BINDINGS` is the single source of the keys: the messages
+//! they send, the help popup and the hints of the bar of keys are all taken from it
+
+use crate::message::Message;
+use crate::model::{CurrentScreen, Model};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+/// Where a key binding is active
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Context {
+    Main,
+    Secondary,
+    Zoom,
+    Help,
+    Prompt,
+}
+
+impl Context {
+    /// Context of the screen currently shown, below the help popup if open
+    pub fn screen(model: &Model) -> Context {
+        match model.current_screen {
+            CurrentScreen::Main => Context::Main,
+            CurrentScreen::Secondary if model.zoomed => Context::Zoom,
+            CurrentScreen::Secondary => Context::Secondary,
+        }
+    }
+
+    /// Context receiving the keys
+    pub fn of(model: &Model) -> Context {
+        if model.prompt.is_some() {
+            return Context::Prompt;
+        }
+        match model.show_help {
+            true => Context::Help,
+            false => Context::screen(model),
+        }
+    }
+
+    pub fn title(self) -> &'static str {
+        match self {
+            Context::Main => "Groups",
+            Context::Secondary => "Entries",
+            Context::Zoom => "Entry",
+            Context::Help => "Help",
+            Context::Prompt => "Prompt",
+        }
+    }
+}
+
+/// A key, with the modifiers that must be held. Shift is implied by uppercase chars
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Key {
+    pub code: KeyCode,
+    pub modifiers: KeyModifiers,
+}
+
+const fn key(code: KeyCode) -> Key {
+    Key {
+        code,
+        modifiers: KeyModifiers::NONE,
+    }
+}
+
+const fn char(c: char) -> Key {
+    key(KeyCode::Char(c))
+}
+
+const fn ctrl(c: char) -> Key {
+    Key {
+        code: KeyCode::Char(c),
+        modifiers: KeyModifiers::CONTROL,
+    }
+}
+
+impl Key {
+    fn matches(&self, event: &KeyEvent) -> bool {
+        self.code == event.code && self.modifiers == event.modifiers.difference(KeyModifiers::SHIFT)
+    }
+}
+
+/// An action and the keys that trigger it
+pub struct Binding {
+    pub keys: &'static [Key],
+    /// Keys as shown to the user
+    pub label: &'static str,
+    /// Short name shown in the bar of keys at the bottom of the screens, empty for the keys
+    /// only listed in the help
+    pub hint: &'static str,
+    pub description: &'static str,
+    pub contexts: &'static [Context],
+    /// Message sent for the pressed key
+    pub message: fn(KeyCode) -> Option<Message>,
+}
+
+use Context::*;
+
+const DIGITS: [Key; 9] = [
+    char('1'),
+    char('2'),
+    char('3'),
+    char('4'),
+    char('5'),
+    char('6'),
+    char('7'),
+    char('8'),
+    char('9'),
+];
+
+/// Every action of the application, in the order shown in the help popup
+pub static BINDINGS: &[Binding] = &[
+    Binding {
+        keys: &[key(KeyCode::Down), char('j')],
+        label: "↓ j",
+        hint: "",
+        description: "Next item, or scroll down the focused description",
+        contexts: &[Main, Secondary, Zoom],
+        message: |_| Some(Message::NextEntry),
+    },
+    Binding {
+        keys: &[key(KeyCode::Up), char('k')],
+        label: "↑ k",
+        hint: "",
+        description: "Previous item, or scroll up the focused description",
+        contexts: &[Main, Secondary, Zoom],
+        message: |_| Some(Message::PreviousEntry),
+    },
+    Binding {
+        keys: &[key(KeyCode::Right), char('l')],
+        label: "→ l",
+        hint: "",
+        description: "Show the child groups of the selected group",
+        contexts: &[Main],
+        message: |_| Some(Message::Expand),
+    },
+    Binding {
+        keys: &[key(KeyCode::Left), char('h')],
+        label: "← h",
+        hint: "",
+        description: "Hide the child groups, or select the parent group",
+        contexts: &[Main],
+        message: |_| Some(Message::Collapse),
+    },
+    Binding {
+        keys: &[key(KeyCode::Enter)],
+        label: "Enter",
+        hint: "Open",
+        description: "Open the selected group, or show/hide its child groups",
+        contexts: &[Main],
+        message: |_| Some(Message::Enter),
+    },
+    Binding {
+        keys: &[key(KeyCode::Enter)],
+        label: "Enter",
+        hint: "Copy",
+        description: "Copy the selected command, or the marked ones, and quit",
+        contexts: &[Secondary, Zoom],
+        message: |_| Some(Message::Enter),
+    },
+    Binding {
+        keys: &[key(KeyCode::PageDown)],
+        label: "PgDn",
+        hint: "",
+        description: "Scroll down the description",
+        contexts: &[Secondary, Zoom],
+        message: |_| Some(Message::ScrollDown),
+    },
+    Binding {
+        keys: &[key(KeyCode::PageUp)],
+        label: "PgUp",
+        hint: "",
+        description: "Scroll up the description",
+        contexts: &[Secondary, Zoom],
+        message: |_| Some(Message::ScrollUp),
+    },
+    Binding {
+        keys: &[char('w')],
+        label: "w",
+        hint: "Wrap",
+        description: "Wrap or truncate long commands",
+        contexts: &[Secondary],
+        message: |_| Some(Message::ToggleWrap),
+    },
+    Binding {
+        keys: &[char('a')],
+        label: "a",
+        hint: "Installed only",
+        description: "Show or hide the entries whose program is not installed",
+        contexts: &[Main, Secondary],
+        message: |_| Some(Message::ToggleMissing),
+    },
+    Binding {
+        keys: &[key(KeyCode::Tab)],
+        label: "Tab",
+        hint: "Panel",
+        description: "Focus the other panel, the only one shown in narrow terminals",
+        contexts: &[Main, Secondary],
+        message: |_| Some(Message::SwitchPanel),
+    },
+    Binding {
+        keys: &[char('z')],
+        label: "z",
+        hint: "Zoom",
+        description: "Show the selected entry full-screen",
+        contexts: &[Secondary],
+        message: |_| Some(Message::ToggleZoom),
+    },
+    Binding {
+        keys: &[char('z')],
+        label: "z",
+        hint: "Close zoom",
+        description: "Go back to the entry list",
+        contexts: &[Zoom],
+        message: |_| Some(Message::ToggleZoom),
+    },
+    Binding {
+        keys: &[char('s')],
+        label: "s",
+        hint: "Sort",
+        description: "Change the sort order of the list",
+        contexts: &[Main, Secondary],
+        message: |_| Some(Message::Sort),
+    },
+    Binding {
+        keys: &[char('p')],
+        label: "p",
+        hint: "Pin",
+        description: "Pin or unpin the selected item",
+        contexts: &[Main, Secondary, Zoom],
+        message: |_| Some(Message::TogglePin),
+    },
+    Binding {
+        keys: &[char('J')],
+        label: "J",
+        hint: "Move down",
+        description: "Move the selected item down",
+        contexts: &[Main, Secondary],
+        message: |_| Some(Message::MoveDown),
+    },
+    Binding {
+        keys: &[char('K')],
+        label: "K",
+        hint: "Move up",
+        description: "Move the selected item up",
+        contexts: &[Main, Secondary],
+        message: |_| Some(Message::MoveUp),
+    },
+    Binding {
+        keys: &[char('x')],
+        label: "x",
+        hint: "Cut",
+        description: "Cut the selected entry, moved once pasted",
+        contexts: &[Secondary, Zoom],
+        message: |_| Some(Message::Cut),
+    },
+    Binding {
+        keys: &[char('v')],
+        label: "v",
+        hint: "Paste",
+        description: "Paste the cut entry into the selected group",
+        contexts: &[Main],
+        message: |_| Some(Message::Paste),
+    },
+    Binding {
+        keys: &[char('v')],
+        label: "v",
+        hint: "Paste",
+        description: "Paste the cut entry after the selected one",
+        contexts: &[Secondary],
+        message: |_| Some(Message::Paste),
+    },
+    Binding {
+        keys: &[char('y')],
+        label: "y",
+        hint: "Duplicate",
+        description: "Duplicate the selected entry",
+        contexts: &[Secondary],
+        message: |_| Some(Message::Duplicate),
+    },
+    Binding {
+        keys: &[char('m'), char(' ')],
+        label: "m Space",
+        hint: "Mark",
+        description: "Mark or unmark the selected entry, to copy several at once",
+        contexts: &[Secondary, Zoom],
+        message: |_| Some(Message::ToggleMark),
+    },
+    Binding {
+        keys: &[char('M')],
+        label: "M",
+        hint: "Unmark all",
+        description: "Unmark every entry",
+        contexts: &[Main, Secondary, Zoom],
+        message: |_| Some(Message::ClearMarks),
+    },
+    Binding {
+        keys: &[char(';')],
+        label: ";",
+        hint: "Separator",
+        description: "Join the marked commands with newlines, && or ;",
+        contexts: &[Main, Secondary, Zoom],
+        message: |_| Some(Message::CycleSeparator),
+    },
+    Binding {
+        keys: &[char('r')],
+        label: "r",
+        hint: "Reveal",
+        description: "Reveal or mask the secret values",
+        contexts: &[Secondary, Zoom],
+        message: |_| Some(Message::ToggleReveal),
+    },
+    Binding {
+        keys: &[char('S')],
+        label: "S",
+        hint: "Secret",
+        description: "Keep a placeholder of the selected command secret, encrypted",
+        contexts: &[Secondary],
+        message: |_| Some(Message::StoreSecret),
+    },
+    Binding {
+        keys: &[char('D')],
+        label: "D",
+        hint: "Describe",
+        description: "Describe the selected command from its man page or --help",
+        contexts: &[Secondary],
+        message: |_| Some(Message::DescribeEntry),
+    },
+    Binding {
+        keys: &[char('E')],
+        label: "E",
+        hint: "Encrypt",
+        description: "Encrypt the database with a passphrase, or change it",
+        contexts: &[Main],
+        message: |_| Some(Message::EncryptDatabase),
+    },
+    Binding {
+        keys: &[char('u')],
+        label: "u",
+        hint: "Undo",
+        description: "Undo the last change",
+        contexts: &[Main, Secondary, Zoom],
+        message: |_| Some(Message::Undo),
+    },
+    Binding {
+        keys: &[ctrl('r')],
+        label: "Ctrl-R",
+        hint: "Redo",
+        description: "Redo the last undone change",
+        contexts: &[Main, Secondary, Zoom],
+        message: |_| Some(Message::Redo),
+    },
+    Binding {
+        keys: &DIGITS,
+        label: "1-9",
+        hint: "Copy pinned",
+        description: "Copy the nth pinned command and quit",
+        contexts: &[Main, Secondary, Zoom],
+        message: |code| match code {
+            KeyCode::Char(c) => c.to_digit(10).map(|n| Message::CopyPinned(n as usize)),
+            _ => None,
+        },
+    },
+    Binding {
+        keys: &[key(KeyCode::Esc)],
+        label: "Esc",
+        hint: "Back",
+        description: "Go back",
+        contexts: &[Secondary, Zoom],
+        message: |_| Some(Message::Back),
+    },
+    Binding {
+        keys: &[char('?')],
+        label: "?",
+        hint: "Help",
+        description: "Show this help",
+        contexts: &[Main, Secondary, Zoom],
+        message: |_| Some(Message::ToggleHelp),
+    },
+    Binding {
+        keys: &[char('L')],
+        label: "L",
+        hint: "Log",
+        description: "Show or hide the recent log messages",
+        contexts: &[Main, Secondary, Zoom],
+        message: |_| Some(Message::ToggleLog),
+    },
+    Binding {
+        keys: &[char('q')],
+        label: "q",
+        hint: "Quit",
+        description: "Quit",
+        contexts: &[Main, Secondary, Zoom],
+        message: |_| Some(Message::Quit),
+    },
+    Binding {
+        keys: &[key(KeyCode::Down), char('j')],
+        label: "↓ j",
+        hint: "",
+        description: "Scroll down",
+        contexts: &[Help],
+        message: |_| Some(Message::ScrollLines(1)),
+    },
+    Binding {
+        keys: &[key(KeyCode::Up), char('k')],
+        label: "↑ k",
+        hint: "",
+        description: "Scroll up",
+        contexts: &[Help],
+        message: |_| Some(Message::ScrollLines(-1)),
+    },
+    Binding {
+        keys: &[key(KeyCode::PageDown)],
+        label: "PgDn",
+        hint: "",
+        description: "Scroll down a page",
+        contexts: &[Help],
+        message: |_| Some(Message::ScrollDown),
+    },
+    Binding {
+        keys: &[key(KeyCode::PageUp)],
+        label: "PgUp",
+        hint: "",
+        description: "Scroll up a page",
+        contexts: &[Help],
+        message: |_| Some(Message::ScrollUp),
+    },
+    Binding {
+        keys: &[key(KeyCode::Esc), char('?'), char('q')],
+        label: "Esc ? q",
+        hint: "Close",
+        description: "Close the help",
+        contexts: &[Help],
+        message: |_| Some(Message::ToggleHelp),
+    },
+    Binding {
+        keys: &[key(KeyCode::Enter)],
+        label: "Enter",
+        hint: "Confirm",
+        description: "Confirm",
+        contexts: &[Prompt],
+        message: |_| Some(Message::SubmitPrompt),
+    },
+    Binding {
+        keys: &[key(KeyCode::Backspace)],
+        label: "Backspace",
+        hint: "Delete",
+        description: "Delete the last character",
+        contexts: &[Prompt],
+        message: |_| Some(Message::PromptBackspace),
+    },
+    Binding {
+        keys: &[key(KeyCode::Esc)],
+        label: "Esc",
+        hint: "Cancel",
+        description: "Cancel",
+        contexts: &[Prompt],
+        message: |_| Some(Message::CancelPrompt),
+    },
+];
+
+/// Bindings active in a context
+pub fn bindings(context: Context) -> impl Iterator<Item = &'static Binding> {
+    BINDINGS
+        .iter()
+        .filter(move |binding| binding.contexts.contains(&context))
+}
+
+/// Message sent by a key press in a context, if the key is bound. Prompts take every
+/// character as typed text
+pub fn message_for(context: Context, event: &KeyEvent) -> Option<Message> {
+    if let (Prompt, KeyCode::Char(c)) = (context, event.code) {
+        if !event.modifiers.contains(KeyModifiers::CONTROL) {
+            return Some(Message::PromptInput(c));
+        }
+    }
+    bindings(context)
+        .find(|binding| binding.keys.iter().any(|key| key.matches(event)))
+        .and_then(|binding| (binding.message)(event.code))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keys_are_bound_once_per_context() {
+        for context in [Main, Secondary, Zoom, Help, Prompt] {
+            let keys: Vec<Key> = bindings(context)
+                .flat_map(|binding| binding.keys.iter().copied())
+                .collect();
+            for (i, key) in keys.iter().enumerate() {
+                assert!(!keys[i + 1..].contains(key), "{:?} in {:?}", key, context);
+            }
+        }
+    }
+
+    #[test]
+    fn messages_depend_on_context() {
+        let press = |code, modifiers| KeyEvent::new(code, modifiers);
+        let down = press(KeyCode::Char('j'), KeyModifiers::NONE);
+
+        assert_eq!(message_for(Main, &down), Some(Message::NextEntry));
+        assert_eq!(
+            message_for(Main, &press(KeyCode::Char('J'), KeyModifiers::SHIFT)),
+            Some(Message::MoveDown)
+        );
+        assert_eq!(message_for(Help, &down), Some(Message::ScrollLines(1)));
+        assert_eq!(
+            message_for(Main, &press(KeyCode::Char('3'), KeyModifiers::NONE)),
+            Some(Message::CopyPinned(3))
+        );
+        assert_eq!(
+            message_for(Main, &press(KeyCode::Char('?'), KeyModifiers::SHIFT)),
+            Some(Message::ToggleHelp)
+        );
+        assert_eq!(
+            message_for(Main, &press(KeyCode::Char('w'), KeyModifiers::NONE)),
+            None
+        );
+        assert_eq!(
+            message_for(Main, &press(KeyCode::Char('j'), KeyModifiers::CONTROL)),
+            None
+        );
+        assert_eq!(
+            message_for(Main, &press(KeyCode::Char('r'), KeyModifiers::CONTROL)),
+            Some(Message::Redo)
+        );
+        assert_eq!(
+            message_for(Prompt, &press(KeyCode::Char('q'), KeyModifiers::NONE)),
+            Some(Message::PromptInput('q'))
+        );
+        assert_eq!(
+            message_for(Prompt, &press(KeyCode::Esc, KeyModifiers::NONE)),
+            Some(Message::CancelPrompt)
+        );
+    }
+}
@@ -0,0 +1,32 @@
+//! Database of shell commands behind the `memors` terminal application.
+//!
+//! Commands are stored as [`Entry`]s, in [`EntryGroup`]s that can contain other groups.
+//! The database is a JSON file, read and written with the functions of [`model_io`], next
+//! to the usage statistics of every command. The [`Model`] holds a loaded database and
+//! implements what the application does with it: sorting, pinning, reordering, undoing...
+//!
+//! ```
+//! use memors::model_io::{load_entry_groups, save_entry_groups};
+//! use memors::{Entry, EntryGroup};
+//!
+//! let path = std::env::temp_dir().join("memors-doc").join("commands.json");
+//! let entry = Entry {
+//!     command: String::from("git log --oneline"),
+//!     short_info: String::from("Compact history"),
+//!     ..Default::default()
+//! };
+//! save_entry_groups(&path, &[EntryGroup::new(String::from("Git"), vec![entry])])?;
+//!
+//! let entry_groups = load_entry_groups(&path)?;
+//! assert_eq!(entry_groups[0].entries[0].command, "git log --oneline");
+//! # std::fs::remove_dir_all(path.parent().unwrap())?;
+//! # Ok::<(), std::io::Error>(())
+//! ```
+
+pub mod config;
+pub mod lint;
+pub mod model;
+
+pub use model::entry::{self, Entry, EntryGroup};
+pub use model::model_io;
+pub use model::Model;
@@ -0,0 +1,369 @@
+//! Checks of a database file for common mistakes, run by `memors lint`
+
+use crate::config::{LINT_LONG_INFO_MAX, LINT_SHORT_INFO_MAX};
+use crate::entry::{Entry, EntryGroup};
+use crate::model::secrets::{placeholder_spans, placeholders};
+use regex::Regex;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+use std::sync::LazyLock;
+
+/// Placeholders written with another syntax than `<name>`, e.g. `{name}` or `{{name}}`.
+/// Shell variables (`${name}`) are not placeholders, nor is what is inside a `<name>`
+static OTHER_PLACEHOLDER: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"\{\{\s*\w+\s*\}\}|(?:^|[^$\w])(\{\w+\})|\[\[\s*\w+\s*\]\]|%\w+%")
+        .expect("Invalid placeholder pattern")
+});
+
+/// Placeholders of the command written with another syntax than `<name>`
+fn other_placeholders(command: &str) -> Vec<&str> {
+    let spans = placeholder_spans(command);
+    OTHER_PLACEHOLDER
+        .captures_iter(command)
+        .filter_map(|found| found.get(1).or(found.get(0)))
+        .filter(|found| !spans.iter().any(|(span, _)| span.contains(&found.start())))
+        .map(|found| found.as_str())
+        .collect()
+}
+
+/// A problem found in a database
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Diagnostic {
+    /// Line of the entry or group in the file, starting at 1, if found
+    pub line: Option<usize>,
+    /// Kind of problem, e.g. "duplicate-command"
+    pub code: &'static str,
+    pub message: String,
+}
+
+/// How the diagnostics are printed
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    /// One `file:line: code: message` line each
+    #[default]
+    Human,
+    /// A JSON array of objects with the file, line, code and message
+    Json,
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "human" => Ok(Format::Human),
+            "json" => Ok(Format::Json),
+            _ => Err(format!("unknown format '{}' (human, json)", s)),
+        }
+    }
+}
+
+/// Diagnostic of a file, as printed in the JSON format
+#[derive(Serialize)]
+struct Located<'a> {
+    file: &'a str,
+    #[serde(flatten)]
+    diagnostic: &'a Diagnostic,
+}
+
+/// Print the diagnostics of a file in the given format
+pub fn report(file: &str, diagnostics: &[Diagnostic], format: Format) -> String {
+    match format {
+        Format::Human => diagnostics
+            .iter()
+            .map(|diagnostic| match diagnostic.line {
+                Some(line) => format!("{}:{}: {}\n", file, line, diagnostic),
+                None => format!("{}: {}\n", file, diagnostic),
+            })
+            .collect(),
+        Format::Json => {
+            let located: Vec<Located> = diagnostics
+                .iter()
+                .map(|diagnostic| Located { file, diagnostic })
+                .collect();
+            serde_json::to_string_pretty(&located).expect("Serialization failed") + "\n"
+        }
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: {}", self.code, self.message)
+    }
+}
+
+/// Finds the lines of the values of the file, in the order they are asked for. The same
+/// value being found several times, each one is given its next occurrence
+struct Locator<'a> {
+    text: &'a str,
+    /// Occurrences already given for each key and value
+    found: HashMap<(&'static str, String), usize>,
+}
+
+impl<'a> Locator<'a> {
+    fn line_of(&mut self, key: &'static str, value: &str) -> Option<usize> {
+        let json = serde_json::to_string(value).ok()?;
+        let pattern = Regex::new(&format!(r#""{}"\s*:\s*{}"#, key, regex::escape(&json))).ok()?;
+        let n = self.found.entry((key, value.to_string())).or_default();
+        let start = pattern.find_iter(self.text).nth(*n)?.start();
+        *n += 1;
+        Some(self.text[..start].matches('\n').count() + 1)
+    }
+}
+
+/// Check the text of a database file
+pub fn lint(text: &str) -> Vec<Diagnostic> {
+    let entry_groups: Vec<EntryGroup> = match serde_json::from_str(text) {
+        Ok(entry_groups) => entry_groups,
+        Err(e) => {
+            return vec![Diagnostic {
+                line: Some(e.line()),
+                code: "invalid-json",
+                message: e.to_string(),
+            }]
+        }
+    };
+
+    let mut linter = Linter {
+        locator: Locator {
+            text,
+            found: HashMap::new(),
+        },
+        commands: HashMap::new(),
+        diagnostics: vec![],
+    };
+    for entry_group in &entry_groups {
+        linter.check_group(entry_group);
+    }
+    let mut diagnostics = linter.diagnostics;
+    diagnostics.sort_by_key(|diagnostic| diagnostic.line);
+    diagnostics
+}
+
+struct Linter<'a> {
+    locator: Locator<'a>,
+    /// Line of the first entry of each command
+    commands: HashMap<String, Option<usize>>,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl Linter<'_> {
+    fn report(&mut self, line: Option<usize>, code: &'static str, message: String) {
+        self.diagnostics.push(Diagnostic {
+            line,
+            code,
+            message,
+        });
+    }
+
+    /// Check a group, then its entries and child groups, in the order of the file
+    fn check_group(&mut self, entry_group: &EntryGroup) {
+        let line = self
+            .locator
+            .line_of("description", &entry_group.description);
+        let name = &entry_group.description;
+        if entry_group.entries.is_empty() && entry_group.groups.is_empty() {
+            self.report(
+                line,
+                "empty-group",
+                format!("group '{}' has no entries", name),
+            );
+        }
+        if name.trim_end() != name {
+            self.report(
+                line,
+                "trailing-whitespace",
+                format!("description of group '{}'", name.trim_end()),
+            );
+        }
+
+        for entry in &entry_group.entries {
+            self.check_entry(entry);
+        }
+        for child in &entry_group.groups {
+            self.check_group(child);
+        }
+    }
+
+    fn check_entry(&mut self, entry: &Entry) {
+        let line = self.locator.line_of("command", &entry.command);
+        let command = &entry.command;
+
+        match self.commands.get(command) {
+            Some(first) => {
+                let first = first.map_or(String::new(), |line| format!(" (line {})", line));
+                self.report(
+                    line,
+                    "duplicate-command",
+                    format!("'{}' is already stored{}", command, first),
+                );
+            }
+            None => {
+                self.commands.insert(command.clone(), line);
+            }
+        }
+
+        if entry.short_info.trim().is_empty() {
+            self.report(
+                line,
+                "empty-short-info",
+                format!("'{}' has no short description", command),
+            );
+        }
+
+        // Placeholders are documented if their name appears in the descriptions
+        let documentation = format!("{}\n{}", entry.short_info, entry.long_info).to_lowercase();
+        for name in placeholders(command) {
+            if !documentation.contains(&name.to_lowercase()) {
+                self.report(
+                    line,
+                    "undocumented-placeholder",
+                    format!("<{}> of '{}' is not described", name, command),
+                );
+            }
+        }
+        for placeholder in other_placeholders(command) {
+            self.report(
+                line,
+                "placeholder-syntax",
+                format!(
+                    "'{}' in '{}' is not written as a <placeholder>",
+                    placeholder.trim(),
+                    command
+                ),
+            );
+        }
+
+        for (field, text) in [
+            ("command", command),
+            ("short_info", &entry.short_info),
+            ("long_info", &entry.long_info),
+        ] {
+            if text.lines().any(|line| line.trim_end() != line) || text.trim_end() != text {
+                self.report(
+                    line,
+                    "trailing-whitespace",
+                    format!("{} of '{}'", field, command.trim_end()),
+                );
+            }
+        }
+
+        let short_len = entry.short_info.chars().count();
+        if short_len > LINT_SHORT_INFO_MAX {
+            self.report(
+                line,
+                "long-description",
+                format!(
+                    "short_info of '{}' has {} characters, more than {}",
+                    command, short_len, LINT_SHORT_INFO_MAX
+                ),
+            );
+        }
+        let long_len = entry.long_info.chars().count();
+        if long_len > LINT_LONG_INFO_MAX {
+            self.report(
+                line,
+                "long-description",
+                format!(
+                    "long_info of '{}' has {} characters, more than {}",
+                    command, long_len, LINT_LONG_INFO_MAX
+                ),
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn codes(diagnostics: &[Diagnostic]) -> Vec<(Option<usize>, &str)> {
+        diagnostics
+            .iter()
+            .map(|diagnostic| (diagnostic.line, diagnostic.code))
+            .collect()
+    }
+
+    #[test]
+    fn lint_demo_database() {
+        let text = include_str!("../examples/demo.json");
+        assert_eq!(lint(text), vec![]);
+    }
+
+    #[test]
+    fn find_problems_and_their_lines() {
+        let text = r#"[
+  {
+    "description": "Net ",
+    "entries": [
+      {
+        "command": "curl <url>",
+        "short_info": "",
+        "long_info": "Fetch the url"
+      },
+      {
+        "command": "scp {file} <host>:",
+        "short_info": "Copy a file",
+        "long_info": ""
+      },
+      {
+        "command": "curl <url>",
+        "short_info": "Fetch the url again",
+        "long_info": "Same\n"
+      }
+    ],
+    "groups": [{"description": "Empty", "entries": []}]
+  }
+]"#;
+        assert_eq!(
+            codes(&lint(text)),
+            [
+                (Some(3), "trailing-whitespace"),
+                (Some(6), "empty-short-info"),
+                (Some(11), "undocumented-placeholder"),
+                (Some(11), "placeholder-syntax"),
+                (Some(16), "duplicate-command"),
+                (Some(16), "trailing-whitespace"),
+                (Some(21), "empty-group"),
+            ]
+        );
+        assert!(lint(text)[4].message.contains("line 6"));
+
+        let diagnostics = lint("[\n  {\"description\": 1}\n]");
+        assert_eq!(codes(&diagnostics), [(Some(2), "invalid-json")]);
+    }
+
+    #[test]
+    fn placeholder_syntax() {
+        let other = |command: &str| !other_placeholders(command).is_empty();
+        assert!(other("docker run {{image}}"));
+        assert!(other("ssh {host}"));
+        assert!(other("echo %name%"));
+        assert!(!other("echo ${HOME} <file>"));
+        assert!(!other("cp file.{txt,bak}"));
+        // Inside a placeholder, as highlighted and asked for
+        assert!(!other("echo <%name%>"));
+        assert!(other("echo <a> %name%"));
+    }
+
+    #[test]
+    fn report_formats() {
+        let diagnostics = vec![Diagnostic {
+            line: Some(3),
+            code: "empty-group",
+            message: String::from("group 'Net' has no entries"),
+        }];
+        assert_eq!(
+            report("db.json", &diagnostics, Format::Human),
+            "db.json:3: empty-group: group 'Net' has no entries\n"
+        );
+        let json: serde_json::Value =
+            serde_json::from_str(&report("db.json", &diagnostics, Format::Json)).unwrap();
+        assert_eq!(json[0]["file"], "db.json");
+        assert_eq!(json[0]["line"], 3);
+        assert_eq!(json[0]["code"], "empty-group");
+        assert_eq!("json".parse(), Ok(Format::Json));
+    }
+}
@@ -0,0 +1,156 @@
+use crate::config::{LOG_DIR_NAME, LOG_FILES_MAX, LOG_PANEL_SIZE, PROJECT_NAME};
+use color_eyre::eyre::eyre;
+use std::collections::VecDeque;
+use std::fmt::{self, Write};
+use std::path::PathBuf;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+use tracing::field::{Field, Visit};
+use tracing::level_filters::LevelFilter;
+use tracing::{Event, Level, Subscriber};
+use tracing_appender::non_blocking::WorkerGuard;
+use tracing_appender::rolling::{RollingFileAppender, Rotation};
+use tracing_subscriber::layer::{Context, Layer, SubscriberExt};
+use tracing_subscriber::util::SubscriberInitExt;
+
+/// Log message shown in the log panel
+#[derive(Debug, Clone, PartialEq)]
+pub struct LogLine {
+    /// Time since the start of the application
+    pub elapsed: Duration,
+    pub level: Level,
+    /// Message followed by its fields, e.g. "loaded the database file=demo.json"
+    pub message: String,
+}
+
+/// Last log messages, the most recent last
+static RECENT: Mutex<VecDeque<LogLine>> = Mutex::new(VecDeque::new());
+
+static START: OnceLock<Instant> = OnceLock::new();
+
+/// Directory of the log files, e.g. ~/.local/state/memors/logs
+pub fn log_dir() -> Option<PathBuf> {
+    dirs::state_dir()
+        .or_else(dirs::data_local_dir)
+        .map(|dir| dir.join(PROJECT_NAME).join(LOG_DIR_NAME))
+}
+
+/// Write the log messages up to `level` to a file of the state directory, a new one every
+/// day, and keep the last ones for the log panel. The returned guard writes the pending
+/// messages to the file when dropped. The application still starts, without logs, when the
+/// log files cannot be written
+pub fn init(level: LevelFilter) -> color_eyre::Result<Option<WorkerGuard>> {
+    if level == LevelFilter::OFF {
+        return Ok(None);
+    }
+    let appender = match appender() {
+        Ok(appender) => appender,
+        Err(e) => {
+            eprintln!("warning: logging disabled, {}", e);
+            return Ok(None);
+        }
+    };
+    let (writer, guard) = tracing_appender::non_blocking(appender);
+
+    START.get_or_init(Instant::now);
+    tracing_subscriber::registry()
+        .with(level)
+        .with(tracing_subscriber::fmt::layer().with_writer(writer))
+        .with(PanelLayer)
+        .try_init()?;
+    Ok(Some(guard))
+}
+
+/// Daily log files of the log directory, created if needed
+fn appender() -> color_eyre::Result<RollingFileAppender> {
+    let dir = log_dir().ok_or_else(|| eyre!("no state directory to write the log files in"))?;
+    std::fs::create_dir_all(&dir).map_err(|e| eyre!("cannot create {}: {}", dir.display(), e))?;
+    Ok(RollingFileAppender::builder()
+        .rotation(Rotation::DAILY)
+        .filename_prefix(PROJECT_NAME)
+        .filename_suffix("log")
+        .max_log_files(LOG_FILES_MAX)
+        .build(dir)?)
+}
+
+/// Last log messages, the most recent last
+pub fn recent() -> Vec<LogLine> {
+    RECENT.lock().unwrap().iter().cloned().collect()
+}
+
+/// Keeps the last log messages for the log panel
+struct PanelLayer;
+
+impl<S: Subscriber> Layer<S> for PanelLayer {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        let mut visitor = MessageVisitor::default();
+        event.record(&mut visitor);
+        let line = LogLine {
+            elapsed: START.get_or_init(Instant::now).elapsed(),
+            level: *event.metadata().level(),
+            message: visitor.message + &visitor.fields,
+        };
+
+        let mut recent = RECENT.lock().unwrap();
+        if recent.len() == LOG_PANEL_SIZE {
+            recent.pop_front();
+        }
+        recent.push_back(line);
+    }
+}
+
+/// Collects the message of a log event, and its other fields as " name=value"
+#[derive(Default)]
+struct MessageVisitor {
+    message: String,
+    fields: String,
+}
+
+impl Visit for MessageVisitor {
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        match field.name() {
+            "message" => self.message = format!("{:?}", value),
+            name => write!(self.fields, " {}={:?}", name, value).unwrap(),
+        }
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        match field.name() {
+            "message" => self.message = value.to_string(),
+            name => write!(self.fields, " {}={}", name, value).unwrap(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keep_recent_messages() {
+        let subscriber = tracing_subscriber::registry().with(PanelLayer);
+        tracing::subscriber::with_default(subscriber, || {
+            tracing::warn!(file = "demo.json", groups = 3, "loaded the database");
+            for i in 0..LOG_PANEL_SIZE {
+                tracing::debug!("message {}", i);
+            }
+        });
+
+        let lines = recent();
+        assert_eq!(lines.len(), LOG_PANEL_SIZE);
+        assert_eq!(lines[0].message, "message 0");
+        assert_eq!(lines.last().unwrap().level, Level::DEBUG);
+
+        // The oldest messages are dropped
+        let subscriber = tracing_subscriber::registry().with(PanelLayer);
+        tracing::subscriber::with_default(subscriber, || {
+            tracing::error!(file = "demo.json", "cannot read the database");
+        });
+        let lines = recent();
+        assert_eq!(lines[0].message, "message 1");
+        assert_eq!(
+            lines.last().unwrap().message,
+            "cannot read the database file=demo.json"
+        );
+    }
+}
@@ -1,143 +1,704 @@
-mod config;
+mod clipboard;
+#[cfg(test)]
+mod harness;
+mod keymap;
+mod logging;
 mod message;
-mod model;
+mod recording;
 mod ui;
 
-use model::entry;
+use memors::model_io::is_encrypted;
+use memors::{config, lint, model};
+use ui::help_popup::render_help_popup;
+use ui::log_panel::render_log_panel;
 use ui::main_screen::render_main_screen;
+use ui::prompt_popup::render_prompt_popup;
 use ui::secondary_screen::render_secondary_screen;
+use ui::theme::Theme;
 use ui::tui;
+use ui::zoom_screen::render_zoom_screen;
+use ui::{Hit, Rendered};
 
 use arboard::Clipboard;
-use crossterm::event::{self, Event, KeyCode};
+use config::{Config, CLIPBOARD_DAEMON_VAR, DOUBLE_CLICK_INTERVAL, MOUSE_SCROLL_LINES};
+use crossterm::event::{self, Event, MouseButton, MouseEvent, MouseEventKind};
+use keymap::Context;
 use message::Message;
-use model::{CurrentScreen, Model, RunningState};
+use model::danger::DangerRules;
+use model::prompt::Prompt;
+use model::secrets::SecretAction;
+use model::usage::SortMode;
+use model::{CurrentScreen, Model, Panel, RunningState};
+use ratatui::backend::TestBackend;
 use ratatui::prelude::*;
-use std::time::Duration;
+use recording::{load_records, Record, Recorder};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use tracing::level_filters::LevelFilter;
 
-use clap::Parser;
+use clap::{Parser, Subcommand};
 
 #[derive(Parser, Debug)]
-#[command(version, about)]
+#[command(version, about, subcommand_negates_reqs = true)]
 struct Args {
+    #[command(subcommand)]
+    command: Option<Command>,
+
     /// TODO: set cache thing (default value?)
+    #[arg(short, long, required = true)]
+    filename: Option<String>,
+
+    /// Do not import the tasks declared in the current directory
+    /// (Makefile, justfile, package.json, cargo aliases)
+    #[arg(long)]
+    no_project_scan: bool,
+
+    /// Initial order of the groups and entries
+    /// (manual, alphabetical, most-used, most-recent, frecency)
+    #[arg(short, long, default_value = "manual")]
+    sort: SortMode,
+
+    /// Configuration file [default: ~/.config/memors/config.toml]
     #[arg(short, long)]
-    filename: String,
+    config: Option<PathBuf>,
+
+    /// Show a compact picker of LINES lines below the prompt instead of taking over
+    /// the whole terminal [default: 12]
+    #[arg(long, value_name = "LINES", num_args = 0..=1, default_missing_value = "12")]
+    inline: Option<u16>,
+
+    /// Write every input event and the message it led to, with its time, to FILE
+    #[arg(long, value_name = "FILE")]
+    record: Option<PathBuf>,
+
+    /// Send the messages recorded in FILE with --record again, at the same pace, before
+    /// going on with the keyboard. Nothing is saved, and the replay stops at the prompts of
+    /// passphrases, which are not recorded
+    #[arg(long, value_name = "FILE")]
+    replay: Option<PathBuf>,
+
+    /// Replay without terminal nor waiting, then print the last frame
+    #[arg(long, requires = "replay")]
+    headless: bool,
+
+    /// Level of the messages written to the log file and shown in the log panel
+    /// (off, error, warn, info, debug, trace)
+    #[arg(long, value_name = "LEVEL", default_value = "info")]
+    log_level: LevelFilter,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Check a database for duplicate commands, missing or badly written descriptions and
+    /// placeholders. Exits with an error if anything is found
+    Lint {
+        /// Database file [default: the one given with --filename]
+        file: Option<PathBuf>,
+
+        /// Output format (human, json)
+        #[arg(long, default_value = "human")]
+        format: lint::Format,
+    },
 }
 
 fn main() -> color_eyre::Result<()> {
+    // Child process clearing the copied secrets, see `clipboard::copy_secret`
+    if let Ok(timeout) = std::env::var(CLIPBOARD_DAEMON_VAR) {
+        return clipboard::run_daemon(&timeout);
+    }
+
     // Get command line arguments
     let args = Args::parse();
+    if let Some(Command::Lint { file, format }) = args.command {
+        let file = file
+            .or(args.filename.map(PathBuf::from))
+            .ok_or_else(|| color_eyre::eyre::eyre!("no database file to check"))?;
+        return run_lint(&file, format);
+    }
+
+    // Log to a file, the terminal being taken by the interface
+    let _log_guard = logging::init(args.log_level)?;
+
+    // Load user configuration
+    let config = match args.config.or_else(Config::default_path) {
+        Some(path) => Config::load(&path)?,
+        None => Config::default(),
+    };
+    let theme = Theme::from_config(&config)?;
+
+    // Sessions recorded to be replayed
+    let records = match &args.replay {
+        Some(path) => load_records(path)?,
+        None => vec![],
+    };
+
+    // Initialise model
+    let mut model = Model::new(args.filename.expect("Required without subcommand"));
+    if !args.no_project_scan {
+        model.project_dir = std::env::current_dir().ok();
+    }
+    model.separator = config.separator;
+    model.danger = DangerRules::new(&config.dangerous_patterns)?;
+    model.config = config;
+    model.compact = args.inline.is_some();
+    model.group_sort = args.sort;
+    model.entry_sort = args.sort;
+    model.dry_run = args.replay.is_some();
+
+    if args.headless {
+        return replay_headless(&mut model, &theme, records);
+    }
 
     // Initialise terminal
     tui::install_panic_hook()?;
-    let mut terminal = tui::init_terminal()?;
+    let mut terminal = tui::init_terminal(args.inline)?;
 
     // Initialise clipboard
     let mut clipboard = Clipboard::new().unwrap();
 
-    // Initialise model
-    let mut model = Model::new(args.filename);
+    // Record the session, starting with the size of the terminal
+    let mut recorder = match &args.record {
+        Some(path) => {
+            let mut recorder = Recorder::create(path)?;
+            let size = terminal.get_frame().size();
+            recorder.record(Some(Event::Resize(size.width, size.height)), None)?;
+            Some(recorder)
+        }
+        None => None,
+    };
+
+    // Last click, to detect double clicks
+    let mut last_click: Option<(Instant, Hit)> = None;
+
+    // Recorded messages are sent first, when they were in the recorded session
+    let start = Instant::now();
+    let mut replayed = records.into_iter();
 
     // Main loop
     while model.running_state != RunningState::Done {
-        // Render the current view
-        terminal.draw(|f| view(&model, f))?;
-
-        // Handle events and map to a Message
-        let mut current_msg = handle_event(&model)?;
+        if awaits_unrecorded_input(&model) && replayed.len() > 0 {
+            tracing::warn!(left = replayed.len(), "replay stopped at a prompt");
+            model.status = Some(String::from("Replay stopped, passphrases are not recorded"));
+            replayed = vec![].into_iter();
+        }
+        let rendered = draw(&mut terminal, &mut model, &theme)?;
 
-        // Process updates as long as they return a non-None message
-        while current_msg.is_some() {
-            current_msg = update(&mut model, current_msg.unwrap());
+        // Handle events and map to a Message. Follow-up messages are not recorded, as the
+        // replayed messages send them again
+        if let Some(msg) = follow_up(&model) {
+            process(&mut model, Some(msg));
+            continue;
+        }
+        let (event, current_msg) = match replayed.next() {
+            Some(record) => {
+                let time = start + Duration::from_millis(record.millis);
+                std::thread::sleep(time.saturating_duration_since(Instant::now()));
+                (record.event, record.message)
+            }
+            None => match model.running_state {
+                RunningState::Empty => (None, Some(Message::Init)),
+                _ => {
+                    let event = read_event()?;
+                    if let Some(event) = event.as_ref().filter(|_| model.prompt.is_none()) {
+                        tracing::trace!(?event, "event");
+                    }
+                    let msg = event
+                        .clone()
+                        .and_then(|event| handle_event(&model, &rendered, &mut last_click, event));
+                    (event, msg)
+                }
+            },
+        };
+        // Passphrases and secret values are not written down
+        let hidden = model.prompt.as_ref().is_some_and(Prompt::masked)
+            && matches!(current_msg, Some(Message::PromptInput(_)));
+        if let Some(recorder) = recorder.as_mut().filter(|_| !hidden) {
+            if event.is_some() || current_msg.is_some() {
+                recorder.record(event, current_msg.clone())?;
+            }
         }
+        process(&mut model, current_msg);
     }
 
-    clipboard.set_text(
-        model.entries[model.idx_entrygroup].entries[model.idx_entry]
-            .command
-            .clone(),
-    )?;
+    // Secrets only stay in the clipboard for a while
+    if let Some(text) = model.text_to_copy() {
+        let timeout = model.config.clipboard_timeout;
+        if model.copies_secrets() && timeout > 0 {
+            clipboard::copy_secret(&text, timeout)
+                .inspect_err(|e| tracing::error!("cannot copy the secrets: {}", e))?;
+        } else {
+            clipboard
+                .set_text(text)
+                .inspect_err(|e| tracing::error!("cannot copy to the clipboard: {}", e))?;
+        }
+    }
 
-    // Close and exit
+    // Close and exit. Inline, the picker is erased to give the prompt back
+    if model.compact {
+        terminal.clear()?;
+    }
     tui::restore_terminal()?;
+    match model.failure {
+        Some(failure) => Err(color_eyre::eyre::eyre!(failure)),
+        None => Ok(()),
+    }
+}
+
+/// Print the problems found in a database file, and exit with an error if there are any
+fn run_lint(file: &Path, format: lint::Format) -> color_eyre::Result<()> {
+    if is_encrypted(file)? {
+        color_eyre::eyre::bail!("{}: encrypted databases cannot be checked", file.display());
+    }
+    let text = std::fs::read_to_string(file)?;
+    let diagnostics = lint::lint(&text);
+    print!(
+        "{}",
+        lint::report(&file.to_string_lossy(), &diagnostics, format)
+    );
+    if !diagnostics.is_empty() {
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+/// Send the recorded messages to a terminal of the recorded size, without waiting, and print
+/// its last frame and what is copied, if anything
+fn replay_headless(
+    model: &mut Model,
+    theme: &Theme,
+    records: Vec<Record>,
+) -> color_eyre::Result<()> {
+    let mut terminal = Terminal::new(TestBackend::new(80, 24))?;
+    let mut stopped = false;
+    for record in records {
+        if awaits_unrecorded_input(model) {
+            stopped = true;
+            break;
+        }
+        if let Some(Event::Resize(width, height)) = record.event {
+            terminal.backend_mut().resize(width, height);
+        }
+        draw(&mut terminal, model, theme)?;
+        process(model, record.message);
+        while let Some(msg) = follow_up(model) {
+            process(model, Some(msg));
+        }
+    }
+    draw(&mut terminal, model, theme)?;
+
+    print!("{}", terminal.backend());
+    if let Some(failure) = model.failure.take() {
+        color_eyre::eyre::bail!(failure);
+    }
+    if stopped {
+        color_eyre::eyre::bail!("replay stopped at a prompt, passphrases are not recorded");
+    }
+    if model.running_state == RunningState::Done {
+        if let Some(text) = model.text_to_copy() {
+            println!("{}", text);
+        }
+    }
     Ok(())
 }
 
+/// Message the application sends itself once the view is drawn, e.g. to show a status
+/// before a slow task
+fn follow_up(model: &Model) -> Option<Message> {
+    model
+        .reading_documentation
+        .then_some(Message::ReadDocumentation)
+}
+
+/// Whether a passphrase or a secret value is asked. What is typed there is not recorded, so a
+/// replay cannot go on
+fn awaits_unrecorded_input(model: &Model) -> bool {
+    model.prompt.as_ref().is_some_and(Prompt::masked)
+}
+
+/// Render the current view, and keep in the model what is only known after rendering
+fn draw<B: Backend>(
+    terminal: &mut Terminal<B>,
+    model: &mut Model,
+    theme: &Theme,
+) -> std::io::Result<Rendered> {
+    let mut rendered = Rendered::default();
+    terminal.draw(|f| rendered = view(model, theme, f))?;
+    if let Some(scroll) = rendered.long_info_scroll {
+        model.long_info_scroll = scroll;
+    }
+    if let Some(scroll) = rendered.help_scroll {
+        model.help_scroll = scroll;
+    }
+    Ok(rendered)
+}
+
+/// Update the model with a message, if any, and with the messages it leads to
+fn process(model: &mut Model, msg: Option<Message>) {
+    // Status messages and confirmations last until the next action
+    if msg.is_some() {
+        model.status = None;
+        model.expire_confirmation();
+    }
+
+    // Process updates as long as they return a non-None message
+    let mut current_msg = msg;
+    while let Some(msg) = current_msg {
+        // Typed text may be a passphrase
+        if !matches!(msg, Message::PromptInput(_)) {
+            tracing::debug!(?msg, "update");
+        }
+        current_msg = update(model, msg);
+    }
+}
+
 /// Render screen given the state of the model
-fn view(model: &Model, f: &mut Frame) {
-    match model.current_screen {
+fn view(model: &Model, theme: &Theme, f: &mut Frame) -> Rendered {
+    let mut rendered = match model.current_screen {
         CurrentScreen::Main => match model.running_state {
-            RunningState::Empty => (),
-            _ => render_main_screen(model, f),
+            RunningState::Empty => return Rendered::default(),
+            // Nothing is shown below the passphrase of an encrypted database
+            _ if model.entries.is_empty() => Rendered::default(),
+            _ => render_main_screen(model, theme, f),
         },
-        CurrentScreen::Secondary => render_secondary_screen(model, f),
+        CurrentScreen::Secondary if model.zoomed => render_zoom_screen(model, theme, f),
+        CurrentScreen::Secondary => render_secondary_screen(model, theme, f),
+    };
+    if model.show_log {
+        render_log_panel(model, theme, f, &mut rendered);
+    }
+    if model.show_help {
+        render_help_popup(model, theme, f, &mut rendered);
+    }
+    if let Some(prompt) = &model.prompt {
+        render_prompt_popup(model, prompt, theme, f, &mut rendered);
     }
+    rendered
 }
 
 fn update(model: &mut Model, msg: Message) -> Option<Message> {
     match msg {
-        // Load the possible cache file
+        // Load the possible cache file, the project tasks and the recently used commands
         // and updates the model
-        Message::Init => {
-            model.load_from_cache();
-        }
+        // Encrypted databases ask their passphrase first, then send Init again
+        Message::Init => match model.load_from_cache() {
+            Ok(true) => {
+                if let Some(dir) = model.project_dir.clone() {
+                    model.scan_project(&dir);
+                }
+                model.add_recent_group();
+                model.check_programs();
+                model.idx_entrygroup = model.group_order().first().copied().unwrap_or(0);
+            }
+            Ok(false) => (),
+            Err(e) => fail(model, "cannot load the database", e),
+        },
         // If main screen: go to next entry group
-        // If secondary screen: go to next entry
-        Message::NextEntry => {
-            model.next_entry();
-        }
+        // If secondary screen: go to next entry, or scroll the description if focused
+        Message::NextEntry => match model.focus {
+            Panel::Description if model.current_screen == CurrentScreen::Secondary => {
+                return Some(Message::ScrollLines(1));
+            }
+            _ => model.next_entry(),
+        },
         // If main screen: go to previous entry group
-        // If secondary screen: go to previous entry
-        Message::PreviousEntry => {
-            model.previous_entry();
+        // If secondary screen: go to previous entry, or scroll the description if focused
+        Message::PreviousEntry => match model.focus {
+            Panel::Description if model.current_screen == CurrentScreen::Secondary => {
+                return Some(Message::ScrollLines(-1));
+            }
+            _ => model.previous_entry(),
+        },
+        // Show or hide the child groups of the selected group
+        Message::Expand => {
+            model.expand();
+        }
+        Message::Collapse => {
+            model.collapse();
+        }
+        // Switch to the next sort mode of the current list
+        Message::Sort => {
+            model.cycle_sort_mode();
+        }
+        // If main screen: pin/unpin the selected entry group
+        // If secondary screen: pin/unpin the selected entry
+        Message::TogglePin => {
+            model.toggle_pin();
+        }
+        // Revert or apply again the last change to the entries
+        Message::Undo => {
+            model.undo();
+        }
+        Message::Redo => {
+            model.redo();
+        }
+        // Reorder the groups and entries, or move entries between groups
+        Message::MoveUp => {
+            model.move_selected(false);
+        }
+        Message::MoveDown => {
+            model.move_selected(true);
         }
-        // Go to the secondary screen
+        Message::Cut => {
+            model.cut_entry();
+        }
+        Message::Paste => {
+            model.paste_entry();
+        }
+        Message::Duplicate => {
+            model.duplicate_entry();
+        }
+        // Select several entries to copy them together
+        Message::ToggleMark => {
+            model.toggle_mark();
+        }
+        Message::ClearMarks => {
+            model.clear_marks();
+        }
+        Message::CycleSeparator => {
+            model.cycle_separator();
+        }
+        // Copy the nth pinned entry, if any, and exit
+        Message::CopyPinned(n) => {
+            if model.select_pinned(n) {
+                model.current_screen = CurrentScreen::Secondary;
+                // Only the pinned entry is copied, not the marked ones. They are kept
+                // while the copy waits for a passphrase or a confirmation
+                let marked = std::mem::take(&mut model.marked);
+                let next = match model.copies_secrets()
+                    && !model.unlock_for(SecretAction::CopyPinned(n))
+                {
+                    true => None,
+                    false => update(model, Message::Enter),
+                };
+                if model.running_state != RunningState::Done {
+                    model.marked = marked;
+                }
+                return next;
+            }
+        }
+        // Scroll the help popup, or the long description of the selected entry
+        Message::ScrollDown => {
+            model.active_scroll().page_down();
+        }
+        Message::ScrollUp => {
+            model.active_scroll().page_up();
+        }
+        Message::ScrollLines(lines) => {
+            model.active_scroll().scroll_lines(lines);
+        }
+        // Select what was clicked, or focus the clicked panel
+        Message::Click(hit) => match hit {
+            Hit::Panel(panel) => model.focus = panel,
+            Hit::Group(idx) if model.current_screen == CurrentScreen::Main => {
+                model.idx_entrygroup = idx;
+            }
+            Hit::Entry(idx) if model.current_screen == CurrentScreen::Secondary => {
+                model.idx_entry = idx;
+                model.long_info_scroll.reset();
+                model.focus = Panel::Entries;
+            }
+            _ => (),
+        },
+        // Select and go into the clicked group, or copy the clicked entry
+        Message::DoubleClick(hit) => match hit {
+            Hit::Group(_) | Hit::Entry(_) => {
+                update(model, Message::Click(hit));
+                return Some(Message::Enter);
+            }
+            Hit::Pinned(n) => return Some(Message::CopyPinned(n)),
+            Hit::Panel(_) | Hit::Help | Hit::Log => (),
+        },
+        // Wrap or truncate long commands in the entry list
+        Message::ToggleWrap => {
+            model.wrap_commands = !model.wrap_commands;
+        }
+        // Focus the other panel, which is the only one shown in narrow terminals
+        Message::SwitchPanel => {
+            model.focus = match model.focus {
+                Panel::Entries => Panel::Description,
+                Panel::Description => Panel::Entries,
+            };
+        }
+        // Show or hide the recent log messages
+        Message::ToggleLog => {
+            model.show_log = !model.show_log;
+        }
+        // Show the secret values instead of masking them, or keep a placeholder secret.
+        // Both ask the passphrase of the secrets first
+        Message::ToggleReveal => {
+            model.toggle_reveal();
+        }
+        Message::StoreSecret => {
+            model.start_storing_secret();
+        }
+        // Propose a long description generated from the documentation of the program
+        Message::DescribeEntry => {
+            model.propose_long_info();
+        }
+        // Sent once the status telling that the documentation is read is shown
+        Message::ReadDocumentation => {
+            model.read_documentation();
+        }
+        // Show or hide the entries whose program is not installed
+        Message::ToggleMissing => {
+            model.toggle_hide_missing();
+        }
+        // Ask the passphrase the database is saved with from now on
+        Message::EncryptDatabase => {
+            model.start_encrypting_database();
+        }
+        // Type in the prompt, then do the action that was waiting for the passphrase
+        Message::PromptInput(c) => {
+            model.prompt_input(c);
+        }
+        Message::PromptBackspace => {
+            model.prompt_backspace();
+        }
+        Message::CancelPrompt => {
+            model.cancel_prompt();
+        }
+        Message::SubmitPrompt => {
+            return model.submit_prompt().map(|action| match action {
+                SecretAction::OpenDatabase => Message::Init,
+                SecretAction::Reveal => Message::ToggleReveal,
+                SecretAction::Copy => Message::Enter,
+                SecretAction::CopyPinned(n) => Message::CopyPinned(n),
+                SecretAction::Store => Message::StoreSecret,
+            });
+        }
+        // Show or hide the actions of the current screen
+        Message::ToggleHelp => {
+            model.show_help = !model.show_help;
+            model.help_scroll.reset();
+        }
+        // Show the selected entry full-screen, or go back to the entry list
+        Message::ToggleZoom => {
+            if model.current_screen == CurrentScreen::Secondary {
+                model.zoomed = !model.zoomed;
+                model.long_info_scroll.reset();
+            }
+        }
+        // Go to the secondary screen, or show/hide the child groups of a group without entries
+        // If secondary screen: record the usage of the selected entry, save and exit
         Message::Enter => match model.current_screen {
             CurrentScreen::Main => {
-                model.current_screen = CurrentScreen::Secondary;
+                let idx = model.idx_entrygroup;
+                if !model.entry_order().is_empty() {
+                    model.current_screen = CurrentScreen::Secondary;
+                    model.focus = Panel::Entries;
+                    model.select_first_entry();
+                } else if model.has_children(idx) {
+                    model.entries[idx].collapsed = !model.entries[idx].collapsed;
+                }
             }
             CurrentScreen::Secondary => {
-                model.running_state = RunningState::Done;
+                if model.copies_secrets() && !model.unlock_for(SecretAction::Copy) {
+                    return None;
+                }
+                if !model.confirm_copy() {
+                    return None;
+                }
+                model.record_usage();
+                if let Err(e) = model.save_to_cache() {
+                    fail(model, "cannot save the database", e);
+                }
             }
         },
-        // Go to the main screen
+        // Close the zoom, or go to the main screen. The secrets are masked again
         Message::Back => {
-            model.current_screen = CurrentScreen::Main;
+            model.reveal_secrets = false;
+            if model.zoomed {
+                model.zoomed = false;
+            } else {
+                model.current_screen = CurrentScreen::Main;
+                model.focus = Panel::Entries;
+            }
         }
         // Save current status to cache
         // and exit
         Message::Quit => {
-            model.save_to_cache();
+            if let Err(e) = model.save_to_cache() {
+                fail(model, "cannot save the database", e);
+            }
         }
     };
     None
 }
 
+/// Quit because of an error of the database, reported once the terminal is restored
+fn fail(model: &mut Model, context: &str, e: std::io::Error) {
+    tracing::error!(file = %model.file, "{}: {}", context, e);
+    model.failure = Some(format!("{} {}: {}", context, model.file, e));
+    model.running_state = RunningState::Done;
+}
+
+/// Wait a bit for the next input event
+fn read_event() -> std::io::Result<Option<Event>> {
+    match event::poll(Duration::from_millis(250))? {
+        true => event::read().map(Some),
+        false => Ok(None),
+    }
+}
+
 /// Convert general Event to Message
-fn handle_event(model: &Model) -> color_eyre::Result<Option<Message>> {
-    if model.running_state == RunningState::Empty {
-        return Ok(Some(Message::Init));
+fn handle_event(
+    model: &Model,
+    rendered: &Rendered,
+    last_click: &mut Option<(Instant, Hit)>,
+    event: Event,
+) -> Option<Message> {
+    match event {
+        Event::Key(key) if key.kind == event::KeyEventKind::Press => handle_key(model, key),
+        Event::Mouse(mouse) => handle_mouse(mouse, rendered, last_click),
+        _ => None,
     }
+}
 
-    if event::poll(Duration::from_millis(250))? {
-        if let Event::Key(key) = event::read()? {
-            if key.kind == event::KeyEventKind::Press {
-                return Ok(handle_key(key));
+/// Convert MouseEvent to Message, hit-testing the areas of the last rendered frame
+fn handle_mouse(
+    mouse: MouseEvent,
+    rendered: &Rendered,
+    last_click: &mut Option<(Instant, Hit)>,
+) -> Option<Message> {
+    let hit = rendered.hit_at(mouse.column, mouse.row)?;
+
+    match mouse.kind {
+        MouseEventKind::Down(MouseButton::Left) => {
+            let now = Instant::now();
+            let double_click = last_click.is_some_and(|(time, last_hit)| {
+                last_hit == hit && now.duration_since(time) <= DOUBLE_CLICK_INTERVAL
+            });
+            match double_click {
+                true => {
+                    *last_click = None;
+                    Some(Message::DoubleClick(hit))
+                }
+                false => {
+                    *last_click = Some((now, hit));
+                    Some(Message::Click(hit))
+                }
             }
         }
+        // The wheel scrolls the description under the pointer, or moves through the list
+        MouseEventKind::ScrollDown => match hit {
+            Hit::Panel(Panel::Description) | Hit::Help => {
+                Some(Message::ScrollLines(MOUSE_SCROLL_LINES))
+            }
+            Hit::Log => None,
+            _ => Some(Message::NextEntry),
+        },
+        MouseEventKind::ScrollUp => match hit {
+            Hit::Panel(Panel::Description) | Hit::Help => {
+                Some(Message::ScrollLines(-MOUSE_SCROLL_LINES))
+            }
+            Hit::Log => None,
+            _ => Some(Message::PreviousEntry),
+        },
+        _ => None,
     }
-    Ok(None)
 }
 
-/// Convert KeyEvent to Message
-fn handle_key(key: event::KeyEvent) -> Option<Message> {
-    match key.code {
-        KeyCode::Char('q') => Some(Message::Quit),
-        KeyCode::Down | KeyCode::Tab | KeyCode::Char('j') => Some(Message::NextEntry),
-        KeyCode::Up | KeyCode::Char('k') => Some(Message::PreviousEntry),
-        KeyCode::Enter => Some(Message::Enter),
-        KeyCode::Esc => Some(Message::Back),
-        _ => None,
-    }
+/// Convert KeyEvent to Message, using the bindings of the context receiving the keys
+fn handle_key(model: &Model, key: event::KeyEvent) -> Option<Message> {
+    keymap::message_for(Context::of(model), &key)
 }
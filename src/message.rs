@@ -1,9 +1,47 @@
+use crate::ui::Hit;
+use serde::{Deserialize, Serialize};
+
 /// Message that is "emitted" given some event
-#[derive(PartialEq, Debug)]
+#[derive(PartialEq, Debug, Clone, Serialize, Deserialize)]
 pub enum Message {
     Init,
     NextEntry,
     PreviousEntry,
+    Expand,
+    Collapse,
+    Sort,
+    TogglePin,
+    Undo,
+    Redo,
+    MoveUp,
+    MoveDown,
+    Cut,
+    Paste,
+    Duplicate,
+    ToggleMark,
+    ClearMarks,
+    CycleSeparator,
+    CopyPinned(usize),
+    ScrollDown,
+    ScrollUp,
+    ScrollLines(i16),
+    Click(Hit),
+    DoubleClick(Hit),
+    ToggleWrap,
+    ToggleMissing,
+    SwitchPanel,
+    ToggleZoom,
+    ToggleHelp,
+    ToggleLog,
+    ToggleReveal,
+    StoreSecret,
+    DescribeEntry,
+    ReadDocumentation,
+    EncryptDatabase,
+    PromptInput(char),
+    PromptBackspace,
+    SubmitPrompt,
+    CancelPrompt,
     Enter,
     Back,
     Quit,
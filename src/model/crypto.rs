@@ -0,0 +1,116 @@
+//! Passphrase-based authenticated encryption, used for the secrets
+
+use argon2::Argon2;
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use std::fmt;
+
+/// Length of the random salt of the key derivation
+pub const SALT_LEN: usize = 16;
+
+const NONCE_LEN: usize = 12;
+
+/// Encrypted data that cannot be decrypted
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CryptoError {
+    /// Not something encrypted by this module
+    Malformed,
+    /// Wrong passphrase, or modified data
+    Decryption,
+}
+
+impl fmt::Display for CryptoError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CryptoError::Malformed => write!(f, "malformed encrypted data"),
+            CryptoError::Decryption => write!(f, "wrong passphrase or corrupted data"),
+        }
+    }
+}
+
+impl std::error::Error for CryptoError {}
+
+/// Key derived from a passphrase with Argon2id, and the salt it was derived with
+#[derive(Clone)]
+pub struct SecretKey {
+    key: Key,
+    pub salt: [u8; SALT_LEN],
+}
+
+impl fmt::Debug for SecretKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("SecretKey").finish_non_exhaustive()
+    }
+}
+
+impl SecretKey {
+    /// Derive a key from the passphrase, with a new random salt
+    pub fn new(passphrase: &str) -> SecretKey {
+        let mut salt = [0; SALT_LEN];
+        chacha20poly1305::aead::rand_core::RngCore::fill_bytes(&mut OsRng, &mut salt);
+        SecretKey::derive(passphrase, salt)
+    }
+
+    /// Derive a key from the passphrase and the salt it was derived with before
+    pub fn derive(passphrase: &str, salt: [u8; SALT_LEN]) -> SecretKey {
+        let mut key = Key::default();
+        Argon2::default()
+            .hash_password_into(passphrase.as_bytes(), &salt, &mut key)
+            .expect("Invalid key derivation parameters");
+        SecretKey { key, salt }
+    }
+
+    /// Encrypt and authenticate the data. The result starts with the salt of the key and
+    /// a random nonce
+    pub fn encrypt(&self, plaintext: &[u8]) -> Vec<u8> {
+        let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng);
+        let ciphertext = ChaCha20Poly1305::new(&self.key)
+            .encrypt(&nonce, plaintext)
+            .expect("Encryption failed");
+        [&self.salt[..], &nonce[..], &ciphertext[..]].concat()
+    }
+
+    /// Decrypt data encrypted with the same passphrase and salt
+    pub fn decrypt(&self, data: &[u8]) -> Result<Vec<u8>, CryptoError> {
+        if salt_of(data)? != self.salt {
+            return Err(CryptoError::Decryption);
+        }
+        let (nonce, ciphertext) = data[SALT_LEN..].split_at(NONCE_LEN);
+        ChaCha20Poly1305::new(&self.key)
+            .decrypt(Nonce::from_slice(nonce), ciphertext)
+            .map_err(|_| CryptoError::Decryption)
+    }
+}
+
+/// Salt of the key that encrypted the data
+pub fn salt_of(data: &[u8]) -> Result<[u8; SALT_LEN], CryptoError> {
+    if data.len() < SALT_LEN + NONCE_LEN {
+        return Err(CryptoError::Malformed);
+    }
+    Ok(data[..SALT_LEN].try_into().unwrap())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encrypt_and_decrypt() {
+        let key = SecretKey::new("correct horse");
+        let data = key.encrypt(b"hunter2");
+        assert_ne!(&data[SALT_LEN + NONCE_LEN..], b"hunter2");
+        assert_eq!(key.decrypt(&data).unwrap(), b"hunter2");
+
+        // The same passphrase and salt give the same key
+        let same_key = SecretKey::derive("correct horse", salt_of(&data).unwrap());
+        assert_eq!(same_key.decrypt(&data).unwrap(), b"hunter2");
+
+        let wrong_key = SecretKey::derive("battery staple", key.salt);
+        assert_eq!(wrong_key.decrypt(&data), Err(CryptoError::Decryption));
+
+        let mut modified = data.clone();
+        *modified.last_mut().unwrap() ^= 1;
+        assert_eq!(key.decrypt(&modified), Err(CryptoError::Decryption));
+        assert_eq!(key.decrypt(&data[..10]), Err(CryptoError::Malformed));
+    }
+}
@@ -0,0 +1,157 @@
+//! Detection of the dangerous commands, that need a confirmation to be copied
+
+use crate::model::Model;
+use regex::Regex;
+
+/// Rules flagging commands as dangerous, one regular expression each
+#[derive(Debug, Clone, Default)]
+pub struct DangerRules {
+    rules: Vec<Regex>,
+}
+
+impl DangerRules {
+    pub fn new(patterns: &[String]) -> Result<DangerRules, regex::Error> {
+        let rules = patterns
+            .iter()
+            .map(|pattern| Regex::new(pattern))
+            .collect::<Result<_, _>>()?;
+        Ok(DangerRules { rules })
+    }
+
+    /// Pattern of the first rule matching the command, if any
+    pub fn matching(&self, command: &str) -> Option<&str> {
+        self.rules
+            .iter()
+            .find(|rule| rule.is_match(command))
+            .map(Regex::as_str)
+    }
+}
+
+/// Copy of dangerous commands waiting to be confirmed
+#[derive(Debug, Default)]
+pub struct Confirmation {
+    /// Copied text that was asked to be confirmed
+    text: Option<String>,
+    /// Whether it was asked by the current action. It only holds for the next one
+    asked_now: bool,
+    /// Whether the copy was confirmed
+    confirmed: bool,
+}
+
+impl Model {
+    /// Pattern of the rule flagging the command as dangerous, if any
+    pub fn danger_of(&self, command: &str) -> Option<&str> {
+        self.danger.matching(command)
+    }
+
+    /// Whether the copied commands can be copied. Dangerous ones have to be copied again
+    /// by the next action: the first time only asks for it, in the status message
+    pub fn confirm_copy(&mut self) -> bool {
+        let Some(text) = self.copied_text() else {
+            return true;
+        };
+        let Some(pattern) = self.danger_of(&text).map(String::from) else {
+            return true;
+        };
+        if self.confirmation.text.as_ref() == Some(&text) {
+            self.confirmation.confirmed = true;
+            return true;
+        }
+
+        self.status = Some(format!(
+            "Dangerous command (matches `{}`), copy it again to confirm",
+            pattern
+        ));
+        tracing::info!(%pattern, "asked to confirm a dangerous copy");
+        self.confirmation = Confirmation {
+            text: Some(text),
+            asked_now: true,
+            confirmed: false,
+        };
+        false
+    }
+
+    /// Forget the copy waiting to be confirmed, unless it was asked by the previous action.
+    /// Called before each new action
+    pub fn expire_confirmation(&mut self) {
+        match self.confirmation.asked_now {
+            true => self.confirmation.asked_now = false,
+            false => self.confirmation.text = None,
+        }
+    }
+
+    /// Text copied when leaving, with the values of the secrets, unless it is dangerous and
+    /// was not confirmed
+    pub fn text_to_copy(&self) -> Option<String> {
+        self.copied_text()
+            .filter(|text| self.confirmation.confirmed || self.danger_of(text).is_none())
+            .and_then(|_| self.copied_text_with_secrets())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::DANGEROUS_PATTERNS;
+    use crate::model::tests::make_test_entry_group;
+    use crate::model::CurrentScreen;
+
+    fn default_rules() -> DangerRules {
+        DangerRules::new(&DANGEROUS_PATTERNS.map(String::from)).unwrap()
+    }
+
+    #[test]
+    fn flag_dangerous_commands() {
+        let rules = default_rules();
+        for command in [
+            "rm -rf build",
+            "rm -f -r build",
+            "git push origin main --force",
+            "git push -f",
+            "git reset --hard HEAD~1",
+            "kubectl delete pod web-0",
+            "psql -c 'DROP TABLE users'",
+            "dd if=image.iso of=/dev/sdb",
+        ] {
+            assert!(rules.matching(command).is_some(), "{}", command);
+        }
+        for command in [
+            "rm file.txt",
+            "git push origin main",
+            "git reset HEAD~1",
+            "kubectl get pods",
+            "cargo run --release",
+        ] {
+            assert_eq!(rules.matching(command), None, "{}", command);
+        }
+
+        assert!(DangerRules::new(&[String::from("(unclosed")]).is_err());
+    }
+
+    #[test]
+    fn confirm_dangerous_copies() {
+        let mut model = Model {
+            entries: vec![make_test_entry_group()],
+            current_screen: CurrentScreen::Secondary,
+            danger: default_rules(),
+            ..Default::default()
+        };
+        model.entries[0].entries[1].command = String::from("rm -rf target");
+        assert!(model.confirm_copy());
+
+        // The first copy only asks for a confirmation
+        model.idx_entry = 1;
+        assert!(!model.confirm_copy());
+        assert!(model.status.is_some());
+        assert_eq!(model.text_to_copy(), None);
+
+        // The confirmation only holds for the next action
+        model.expire_confirmation();
+        model.expire_confirmation();
+        assert!(!model.confirm_copy());
+
+        model.expire_confirmation();
+        assert!(model.confirm_copy());
+        assert_eq!(model.text_to_copy().as_deref(), Some("rm -rf target"));
+    }
+}
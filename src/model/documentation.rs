@@ -0,0 +1,509 @@
+//! Long descriptions generated from the local documentation of the programs: the synopsis
+//! of their man page, or of their `--help` output, and the paragraphs of the flags used
+
+use crate::config::{DOCUMENTATION_TIMEOUT, DOCUMENTATION_WIDTH, LINT_LONG_INFO_MAX};
+use crate::model::programs::{find_program, program_of};
+use crate::model::prompt::{Prompt, PromptKind};
+use crate::model::Model;
+use regex::Regex;
+use std::ffi::OsStr;
+use std::io::Read;
+use std::process::{Command, Stdio};
+use std::sync::LazyLock;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Escape sequences of colors and bold text, sometimes left by `man` and `--help`
+static ESCAPES: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\x1b\[[0-9;]*[A-Za-z]").expect("Invalid escape pattern"));
+
+/// Documentation of the program of a command: the man page of its subcommand, e.g.
+/// `git-commit` for `git commit -a`, or of the program, or else what the program prints for
+/// `--help` if `help` allows running it. Only the programs found in the directories of
+/// `search_path` are run, all of them within the timeout
+pub fn documentation_of(command: &str, search_path: &OsStr, help: bool) -> Option<String> {
+    let deadline = Instant::now() + Duration::from_secs(DOCUMENTATION_TIMEOUT);
+    let program = program_of(command)?;
+    let pages = subcommand_of(command, program)
+        .map(|subcommand| format!("{}-{}", program, subcommand))
+        .into_iter()
+        .chain([program.to_string()]);
+    let man_page = find_program("man", search_path).and_then(|man| {
+        pages.into_iter().find_map(|page| {
+            let mut command = Command::new(&man);
+            command
+                .arg(page)
+                .env("PATH", search_path)
+                .env("MANPAGER", "cat")
+                .env("MANWIDTH", DOCUMENTATION_WIDTH.to_string())
+                .env("GROFF_NO_SGR", "1");
+            run(command, false, deadline)
+        })
+    });
+    man_page
+        .or_else(|| {
+            if !help {
+                return None;
+            }
+            let mut command = Command::new(find_program(program, search_path)?);
+            command.arg("--help");
+            // Some programs print their usage on stderr
+            run(command, true, deadline)
+        })
+        .map(|text| clean(&text))
+}
+
+/// First word after the program that is not a flag, if it can name a subcommand, e.g.
+/// `commit` for `git commit -a` but not `<file>` for `cat <file>`
+fn subcommand_of<'a>(command: &'a str, program: &str) -> Option<&'a str> {
+    command
+        .split_whitespace()
+        .skip_while(|word| *word != program)
+        .skip(1)
+        .find(|word| !word.starts_with('-'))
+        .filter(|word| word.chars().all(|c| c.is_ascii_alphanumeric() || c == '-'))
+}
+
+/// Output of the command, if it succeeds (or prints something, when `lenient`) before the
+/// deadline. It is killed otherwise
+fn run(mut command: Command, lenient: bool, deadline: Instant) -> Option<String> {
+    let mut child = command
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .ok()?;
+
+    // Pipes are read while waiting, as the output may not fit in their buffers
+    let readers = [
+        child
+            .stdout
+            .take()
+            .map(|out| Box::new(out) as Box<dyn Read + Send>),
+        child
+            .stderr
+            .take()
+            .map(|err| Box::new(err) as Box<dyn Read + Send>),
+    ]
+    .map(|pipe| {
+        thread::spawn(move || {
+            let mut text = String::new();
+            if let Some(mut pipe) = pipe {
+                let _ = pipe.read_to_string(&mut text);
+            }
+            text
+        })
+    });
+
+    let status = loop {
+        match child.try_wait() {
+            Ok(Some(status)) => break Some(status),
+            Ok(None) if Instant::now() < deadline => thread::sleep(Duration::from_millis(10)),
+            _ => {
+                let _ = child.kill();
+                let _ = child.wait();
+                break None;
+            }
+        }
+    };
+    let [stdout, stderr] = readers.map(|reader| reader.join().unwrap_or_default());
+    tracing::debug!(?status, "read the documentation");
+
+    let text = match stdout.trim().is_empty() {
+        true => stderr,
+        false => stdout,
+    };
+    match status {
+        Some(status) if !text.trim().is_empty() && (status.success() || lenient) => Some(text),
+        _ => None,
+    }
+}
+
+/// Remove the formatting of terminals: overstruck (bold and underlined) characters, colors
+/// and tabs
+fn clean(text: &str) -> String {
+    let mut cleaned = String::with_capacity(text.len());
+    for c in ESCAPES.replace_all(text, "").chars() {
+        match c {
+            '\u{8}' => {
+                cleaned.pop();
+            }
+            '\t' => cleaned.push_str("        "),
+            '\r' => {}
+            c => cleaned.push(c),
+        }
+    }
+    cleaned
+}
+
+/// Number of spaces starting the line
+fn indent(line: &str) -> usize {
+    line.len() - line.trim_start_matches(' ').len()
+}
+
+/// Lines without their common indentation and the blank lines around them
+fn dedent(lines: &[&str]) -> String {
+    let start = lines.iter().position(|line| !line.trim().is_empty());
+    let end = lines.iter().rposition(|line| !line.trim().is_empty());
+    let (Some(start), Some(end)) = (start, end) else {
+        return String::new();
+    };
+    let lines = &lines[start..=end];
+    let min = lines
+        .iter()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| indent(line))
+        .min()
+        .unwrap_or(0);
+    lines
+        .iter()
+        .map(|line| line.get(min..).unwrap_or("").trim_end())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Lines of a section of a man page, whose title is not indented, e.g. `SYNOPSIS`
+fn section<'a>(lines: &[&'a str], title: &str) -> Option<Vec<&'a str>> {
+    let start = lines.iter().position(|line| line.trim_end() == title)? + 1;
+    let len = lines[start..]
+        .iter()
+        .position(|line| !line.trim().is_empty() && indent(line) == 0)
+        .unwrap_or(lines.len() - start);
+    Some(lines[start..start + len].to_vec())
+}
+
+/// Usage of the program: the synopsis of the man page, or the `Usage:` lines of `--help`
+fn synopsis(lines: &[&str]) -> String {
+    if let Some(synopsis) = section(lines, "SYNOPSIS") {
+        return dedent(&synopsis);
+    }
+    let Some(start) = lines
+        .iter()
+        .position(|line| line.trim_start().to_lowercase().starts_with("usage:"))
+    else {
+        return String::new();
+    };
+    let len = lines[start..]
+        .iter()
+        .position(|line| line.trim().is_empty())
+        .unwrap_or(lines.len() - start);
+    dedent(&lines[start..start + len])
+}
+
+/// Flags written in the list of options of a line, e.g. `-o` and `--output` for
+/// `-o, --output=FILE   write to FILE`
+fn flags_of_line(line: &str) -> Vec<&str> {
+    let line = line.trim();
+    let terms = line.split("  ").next().unwrap_or(line);
+    terms
+        .split([',', ' ', '=', '[', '|'])
+        .filter(|term| term.starts_with('-'))
+        .collect()
+}
+
+/// Paragraph describing the flag in the list of options, from the line introducing it to
+/// the next line indented as much, and the index of that line
+fn paragraph(lines: &[&str], flag: &str) -> Option<(usize, String)> {
+    let start = lines.iter().position(|line| {
+        line.trim_start().starts_with('-') && flags_of_line(line).contains(&flag)
+    })?;
+    let margin = indent(lines[start]);
+    let mut end = start + 1;
+    while let Some(line) = lines.get(end) {
+        let blank = line.trim().is_empty();
+        // A blank line only belongs to the paragraph if the description goes on after it
+        let next = lines[end..].iter().find(|line| !line.trim().is_empty());
+        if (blank && next.is_none_or(|next| indent(next) <= margin))
+            || (!blank && indent(line) <= margin)
+        {
+            break;
+        }
+        end += 1;
+    }
+    Some((start, dedent(&lines[start..end])))
+}
+
+/// Flags of the command, without their value. Combined short flags, e.g. `-xvf`, are
+/// also split in case they are documented separately
+fn flags_of_command(command: &str) -> Vec<String> {
+    let mut flags: Vec<String> = vec![];
+    for word in command.split_whitespace() {
+        if !word.starts_with('-') || word == "-" || word == "--" {
+            continue;
+        }
+        let flag = word.split('=').next().unwrap_or(word);
+        flags.push(flag.to_string());
+        if !flag.starts_with("--") && flag.len() > 2 {
+            flags.extend(flag.chars().skip(1).map(|c| format!("-{}", c)));
+        }
+    }
+    flags.dedup();
+    flags
+}
+
+/// Long description of the command from the documentation of its program: the summary of
+/// the man page, the synopsis and the paragraphs of the flags used, as Markdown. Blocks that
+/// would make it longer than `memors lint` accepts are left out
+pub fn describe(command: &str, documentation: &str) -> String {
+    let lines: Vec<&str> = documentation.lines().collect();
+    let mut blocks: Vec<String> = vec![];
+    if let Some(name) = section(&lines, "NAME") {
+        blocks.push(dedent(&name).lines().collect::<Vec<_>>().join(" "));
+    }
+    let synopsis = synopsis(&lines);
+    if !synopsis.is_empty() {
+        blocks.push(format!("```\n{}\n```", synopsis));
+    }
+
+    // In the order of the documentation
+    let mut paragraphs: Vec<(usize, String)> = flags_of_command(command)
+        .iter()
+        .filter_map(|flag| paragraph(&lines, flag))
+        .collect();
+    paragraphs.sort();
+    paragraphs.dedup();
+    let paragraphs: Vec<String> = paragraphs.into_iter().map(|(_, text)| text).collect();
+    if !paragraphs.is_empty() {
+        blocks.push(format!("```\n{}\n```", paragraphs.join("\n\n")));
+    }
+
+    let mut text = String::new();
+    for block in blocks {
+        if text.chars().count() + block.chars().count() + 2 > LINT_LONG_INFO_MAX {
+            continue;
+        }
+        if !text.is_empty() {
+            text.push_str("\n\n");
+        }
+        text.push_str(&block);
+    }
+    text
+}
+
+impl Model {
+    /// Generate the long description of the selected command from the documentation of its
+    /// program, and propose it in the prompt to be kept or discarded. The documentation is
+    /// only read by `read_documentation`, once the status telling so is shown
+    pub fn propose_long_info(&mut self) {
+        let command = &self.entries[self.idx_entrygroup].entries[self.idx_entry].command;
+        if !self.is_stored(command) {
+            self.status = Some(String::from("Only the stored commands can be described"));
+            return;
+        }
+        let Some(program) = program_of(command) else {
+            self.status = Some(String::from("The program of the command is not known"));
+            return;
+        };
+        if self.search_path.is_none() {
+            self.status = Some(String::from(
+                "PATH is not set, the documentation cannot be looked up",
+            ));
+            return;
+        }
+        self.status = Some(format!("Reading the documentation of {}…", program));
+        self.reading_documentation = true;
+    }
+
+    /// Read the documentation of the selected command asked by `propose_long_info`.
+    /// Dangerous commands only get their man page, their program is not run for `--help`
+    pub fn read_documentation(&mut self) {
+        if !std::mem::take(&mut self.reading_documentation) {
+            return;
+        }
+        let command = &self.entries[self.idx_entrygroup].entries[self.idx_entry].command;
+        let (Some(program), Some(search_path)) = (program_of(command), &self.search_path) else {
+            return;
+        };
+        let help = self.danger_of(command).is_none();
+        let long_info = documentation_of(command, search_path, help)
+            .map(|documentation| describe(command, &documentation))
+            .unwrap_or_default();
+        match long_info.is_empty() {
+            true => self.status = Some(format!("No documentation found for {}", program)),
+            false => {
+                self.status = None;
+                self.prompt = Some(Prompt::new(PromptKind::LongInfo(long_info)));
+            }
+        }
+    }
+
+    /// Replace the long description of the selected command, in every copy of it
+    pub(crate) fn set_long_info(&mut self, long_info: String) {
+        self.edit_every_copy(String::from("generate the description"), |entry| {
+            entry.long_info = long_info.clone();
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::tests::make_test_entry_group;
+    use crate::model::CurrentScreen;
+
+    const MAN_PAGE: &str = "\
+LS(1)                            User Commands                           LS(1)
+
+NAME
+       ls - list directory contents
+
+SYNOPSIS
+       ls [OPTION]... [FILE]...
+
+DESCRIPTION
+       List information about the FILEs (the current directory by default).
+
+       -a, --all
+              do not ignore entries starting with .
+
+       -l     use a long listing format
+
+       --sort=WORD
+              sort by WORD instead of name: none (-U), size (-S), time (-t),
+              version (-v), extension (-X), width
+
+              The default is by name.
+
+       -S     sort by file size, largest first
+
+AUTHOR
+       Written by Richard M. Stallman and David MacKenzie.
+";
+
+    const HELP: &str = "\
+Usage: grep [OPTION]... PATTERNS [FILE]...
+Search for PATTERNS in each FILE.
+
+Pattern selection and interpretation:
+  -E, --extended-regexp     PATTERNS are extended regular expressions
+  -i, --ignore-case         ignore case distinctions in patterns and data
+  -r, --recursive           like --directories=recurse
+";
+
+    #[test]
+    fn describe_from_man_pages() {
+        assert_eq!(
+            describe("ls -la --sort=size <dir>", MAN_PAGE),
+            "ls - list directory contents\n\n\
+             ```\nls [OPTION]... [FILE]...\n```\n\n\
+             ```\n\
+             -a, --all\n       do not ignore entries starting with .\n\n\
+             -l     use a long listing format\n\n\
+             --sort=WORD\n       sort by WORD instead of name: none (-U), size (-S), time (-t),\n       \
+             version (-v), extension (-X), width\n\n       The default is by name.\n\
+             ```"
+        );
+        // Without flags, only the summary and the synopsis
+        assert_eq!(
+            describe("ls", MAN_PAGE),
+            "ls - list directory contents\n\n```\nls [OPTION]... [FILE]...\n```"
+        );
+    }
+
+    #[test]
+    fn describe_from_help() {
+        assert_eq!(
+            describe("grep -ri <pattern> .", HELP),
+            "```\nUsage: grep [OPTION]... PATTERNS [FILE]...\nSearch for PATTERNS in each FILE.\n```\n\n\
+             ```\n\
+             -i, --ignore-case         ignore case distinctions in patterns and data\n\n\
+             -r, --recursive           like --directories=recurse\n\
+             ```"
+        );
+        assert_eq!(describe("grep", "nothing useful"), "");
+        assert_eq!(clean("b\u{8}bold \x1b[1mls\x1b[0m\r"), "bold ls");
+    }
+
+    #[test]
+    fn find_the_subcommand() {
+        assert_eq!(subcommand_of("git commit -a", "git"), Some("commit"));
+        assert_eq!(
+            subcommand_of("sudo -E apt install <package>", "apt"),
+            Some("install")
+        );
+        assert_eq!(subcommand_of("cat <file>", "cat"), None);
+        assert_eq!(subcommand_of("ls -la", "ls"), None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn run_help_of_safe_commands_only() -> std::io::Result<()> {
+        use crate::model::danger::DangerRules;
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = std::env::temp_dir().join("memors-documentation");
+        std::fs::create_dir_all(&dir)?;
+        std::fs::write(
+            dir.join("command1"),
+            "#!/bin/sh\necho 'Usage: command1 [-v]'\n",
+        )?;
+        std::fs::set_permissions(dir.join("command1"), std::fs::Permissions::from_mode(0o755))?;
+        assert!(documentation_of("command1", dir.as_os_str(), false).is_none());
+
+        // The status is shown before the documentation is read
+        let mut model = Model {
+            entries: vec![make_test_entry_group()],
+            current_screen: CurrentScreen::Secondary,
+            search_path: Some(dir.clone().into_os_string()),
+            ..Default::default()
+        };
+        model.propose_long_info();
+        assert_eq!(
+            model.status.as_deref(),
+            Some("Reading the documentation of command1…")
+        );
+        assert!(model.prompt.is_none());
+        model.read_documentation();
+        assert_eq!(
+            model.prompt.map(|prompt| prompt.kind),
+            Some(PromptKind::LongInfo(String::from(
+                "```\nUsage: command1 [-v]\n```"
+            )))
+        );
+
+        // Dangerous commands are not run
+        model.danger = DangerRules::new(&[String::from("^command1")]).unwrap();
+        model.prompt = None;
+        model.propose_long_info();
+        model.read_documentation();
+        assert!(model.prompt.is_none());
+        assert_eq!(
+            model.status.as_deref(),
+            Some("No documentation found for command1")
+        );
+
+        // Cleaning
+        std::fs::remove_dir_all(dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn keep_proposed_long_info() {
+        let mut model = Model {
+            entries: vec![make_test_entry_group()],
+            current_screen: CurrentScreen::Secondary,
+            ..Default::default()
+        };
+        model.set_long_info(String::from("Generated"));
+        assert_eq!(model.entries[0].entries[0].long_info, "Generated");
+        model.undo();
+        assert_ne!(model.entries[0].entries[0].long_info, "Generated");
+
+        // Nothing is looked up without search path
+        model.propose_long_info();
+        assert!(!model.reading_documentation);
+        assert_eq!(
+            model.status.as_deref(),
+            Some("PATH is not set, the documentation cannot be looked up")
+        );
+
+        // Project tasks are not saved
+        model.entries[0].read_only = true;
+        model.propose_long_info();
+        assert!(model.prompt.is_none());
+        assert_eq!(
+            model.status.as_deref(),
+            Some("Only the stored commands can be described")
+        );
+    }
+}
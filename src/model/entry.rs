@@ -1,22 +1,48 @@
+//! Commands and the groups they are stored in
+
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
 /// Entry model. Saves information about the command string that will be returned by the
 /// application, a brief description that will be shown to indicate what that command does,
 /// and a long description that the user can pass to explain more in detail what the command does,
 /// or to give an example, or whatever
-#[derive(Debug, Default, Serialize, Deserialize)]
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Entry {
     pub command: String,
     pub short_info: String,
     pub long_info: String,
+    /// Pinned entries are listed in the quick-access bar of the main screen
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub pinned: bool,
+    /// Values of the secret `<placeholder>`s of the command, indexed by name. They are
+    /// encrypted with the passphrase of the secrets, see `model::secrets`
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub secrets: BTreeMap<String, String>,
 }
 
 /// Entry group model. Saves a global description and a vector of entries
 /// e.g. cmake - 1. create build files 2. build 3. install
-#[derive(Debug, Default, Serialize, Deserialize)]
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
 pub struct EntryGroup {
     pub description: String,
     pub entries: Vec<Entry>,
+    /// Child groups, e.g. "Branching" and "Rebasing" in "Git". Only used in the database
+    /// file: the model keeps every group in a flat list, see `depth`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub groups: Vec<EntryGroup>,
+    /// Pinned groups are listed first in the main screen
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub pinned: bool,
+    /// Virtual groups (e.g. discovered from the current project) are shown but never saved
+    #[serde(skip)]
+    pub read_only: bool,
+    /// Nesting level in the tree of groups. Child groups follow their parent in the model
+    #[serde(skip)]
+    pub depth: usize,
+    /// Whether the child groups are hidden in the main screen
+    #[serde(skip)]
+    pub collapsed: bool,
 }
 
 impl EntryGroup {
@@ -24,10 +50,28 @@ impl EntryGroup {
         EntryGroup {
             description,
             entries,
+            groups: vec![],
+            pinned: false,
+            read_only: false,
+            depth: 0,
+            collapsed: false,
+        }
+    }
+
+    /// Create a virtual group that is rendered but never written to the cache file
+    pub fn new_read_only(description: String, entries: Vec<Entry>) -> EntryGroup {
+        EntryGroup {
+            read_only: true,
+            ..EntryGroup::new(description, entries)
         }
     }
 }
 
+/// Used to keep the default flags out of the database file
+fn is_false(flag: &bool) -> bool {
+    !flag
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -38,6 +82,7 @@ mod tests {
         assert_eq!(entry.command.len(), 0);
         assert_eq!(entry.short_info.len(), 0);
         assert_eq!(entry.long_info.len(), 0);
+        assert!(!entry.pinned);
     }
 
     #[test]
@@ -49,6 +94,7 @@ mod tests {
             command,
             short_info,
             long_info,
+            ..Default::default()
         };
 
         assert_eq!(entry.command, "my_command");
@@ -61,6 +107,58 @@ mod tests {
         let entry = EntryGroup::default();
         assert_eq!(entry.description.len(), 0);
         assert_eq!(entry.entries.len(), 0);
+        assert!(!entry.pinned);
+        assert!(!entry.read_only);
+    }
+
+    #[test]
+    fn pinned_flag_is_optional() {
+        let entry: Entry =
+            serde_json::from_str(r#"{"command": "ls", "short_info": "", "long_info": ""}"#)
+                .unwrap();
+        assert!(!entry.pinned);
+        assert!(!serde_json::to_string(&entry).unwrap().contains("pinned"));
+
+        let entry = Entry {
+            pinned: true,
+            ..Default::default()
+        };
+        assert!(serde_json::to_string(&entry)
+            .unwrap()
+            .contains("\"pinned\":true"));
+    }
+
+    #[test]
+    fn secrets_are_optional() {
+        let entry: Entry =
+            serde_json::from_str(r#"{"command": "ls", "short_info": "", "long_info": ""}"#)
+                .unwrap();
+        assert!(entry.secrets.is_empty());
+        assert!(!serde_json::to_string(&entry).unwrap().contains("secrets"));
+
+        let entry: Entry = serde_json::from_str(
+            r#"{"command": "curl -H <token>", "short_info": "", "long_info": "",
+                "secrets": {"token": "c2VjcmV0"}}"#,
+        )
+        .unwrap();
+        assert_eq!(entry.secrets["token"], "c2VjcmV0");
+    }
+
+    #[test]
+    fn child_groups_are_optional() {
+        let entry_group: EntryGroup =
+            serde_json::from_str(r#"{"description": "Git", "entries": []}"#).unwrap();
+        assert!(entry_group.groups.is_empty());
+        assert!(!serde_json::to_string(&entry_group)
+            .unwrap()
+            .contains("groups"));
+
+        let entry_group: EntryGroup = serde_json::from_str(
+            r#"{"description": "Git", "entries": [],
+                "groups": [{"description": "Rebasing", "entries": []}]}"#,
+        )
+        .unwrap();
+        assert_eq!(entry_group.groups[0].description, "Rebasing");
     }
 
     #[test]
@@ -70,11 +168,13 @@ mod tests {
             command: String::from("my_command1"),
             short_info: String::from("info"),
             long_info: String::from(""),
+            ..Default::default()
         };
         let entry2 = Entry {
             command: String::from("my_command2"),
             short_info: String::from("infooo"),
             long_info: String::from(""),
+            ..Default::default()
         };
 
         let eg = EntryGroup::new(description, vec![entry1, entry2]);
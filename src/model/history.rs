@@ -0,0 +1,404 @@
+//! Undo and redo of the changes to the entry groups
+
+use crate::config::HISTORY_SIZE;
+use crate::entry::{Entry, EntryGroup};
+use crate::model::{CurrentScreen, Model};
+
+/// Editable fields of an entry group, i.e. everything but its entries
+#[derive(Debug, Clone, PartialEq)]
+pub struct GroupInfo {
+    pub description: String,
+    pub pinned: bool,
+}
+
+impl GroupInfo {
+    pub fn of(entry_group: &EntryGroup) -> GroupInfo {
+        GroupInfo {
+            description: entry_group.description.clone(),
+            pinned: entry_group.pinned,
+        }
+    }
+}
+
+/// Elementary edit of the entry groups. Indices are the ones in the model, not the shown order
+#[derive(Debug, Clone, PartialEq)]
+pub enum Edit {
+    InsertGroup {
+        idx: usize,
+        group: EntryGroup,
+    },
+    DeleteGroup {
+        idx: usize,
+        group: EntryGroup,
+    },
+    ModifyGroup {
+        idx: usize,
+        before: GroupInfo,
+        after: GroupInfo,
+    },
+    /// Remove the `len` groups from `from` (a group and its descendants), then insert
+    /// them at `to`
+    MoveGroup {
+        from: usize,
+        len: usize,
+        to: usize,
+    },
+    InsertEntry {
+        group: usize,
+        idx: usize,
+        entry: Entry,
+    },
+    DeleteEntry {
+        group: usize,
+        idx: usize,
+        entry: Entry,
+    },
+    ModifyEntry {
+        group: usize,
+        idx: usize,
+        before: Entry,
+        after: Entry,
+    },
+    /// Remove the entry at `from` (group, entry), then insert it at `to`
+    MoveEntry {
+        from: (usize, usize),
+        to: (usize, usize),
+    },
+}
+
+impl Edit {
+    /// Edit that reverts this one
+    pub fn inverse(&self) -> Edit {
+        match self.clone() {
+            Edit::InsertGroup { idx, group } => Edit::DeleteGroup { idx, group },
+            Edit::DeleteGroup { idx, group } => Edit::InsertGroup { idx, group },
+            Edit::ModifyGroup { idx, before, after } => Edit::ModifyGroup {
+                idx,
+                before: after,
+                after: before,
+            },
+            Edit::MoveGroup { from, len, to } => Edit::MoveGroup {
+                from: to,
+                len,
+                to: from,
+            },
+            Edit::InsertEntry { group, idx, entry } => Edit::DeleteEntry { group, idx, entry },
+            Edit::DeleteEntry { group, idx, entry } => Edit::InsertEntry { group, idx, entry },
+            Edit::ModifyEntry {
+                group,
+                idx,
+                before,
+                after,
+            } => Edit::ModifyEntry {
+                group,
+                idx,
+                before: after,
+                after: before,
+            },
+            Edit::MoveEntry { from, to } => Edit::MoveEntry { from: to, to: from },
+        }
+    }
+
+    /// Apply the edit to the entry groups
+    pub fn apply(&self, entry_groups: &mut Vec<EntryGroup>) {
+        match self.clone() {
+            Edit::InsertGroup { idx, group } => entry_groups.insert(idx, group),
+            Edit::DeleteGroup { idx, .. } => {
+                entry_groups.remove(idx);
+            }
+            Edit::ModifyGroup { idx, after, .. } => {
+                entry_groups[idx].description = after.description;
+                entry_groups[idx].pinned = after.pinned;
+            }
+            Edit::MoveGroup { from, len, to } => {
+                let groups: Vec<EntryGroup> = entry_groups.drain(from..from + len).collect();
+                entry_groups.splice(to..to, groups);
+            }
+            Edit::InsertEntry { group, idx, entry } => {
+                entry_groups[group].entries.insert(idx, entry)
+            }
+            Edit::DeleteEntry { group, idx, .. } => {
+                entry_groups[group].entries.remove(idx);
+            }
+            Edit::ModifyEntry {
+                group, idx, after, ..
+            } => entry_groups[group].entries[idx] = after,
+            Edit::MoveEntry { from, to } => {
+                let entry = entry_groups[from.0].entries.remove(from.1);
+                entry_groups[to.0].entries.insert(to.1, entry);
+            }
+        }
+    }
+
+    /// Group and entry where the edit took place, to select them once applied
+    fn location(&self) -> (usize, Option<usize>) {
+        match *self {
+            Edit::InsertGroup { idx, .. }
+            | Edit::DeleteGroup { idx, .. }
+            | Edit::ModifyGroup { idx, .. }
+            | Edit::MoveGroup { to: idx, .. } => (idx, None),
+            Edit::InsertEntry { group, idx, .. }
+            | Edit::DeleteEntry { group, idx, .. }
+            | Edit::ModifyEntry { group, idx, .. }
+            | Edit::MoveEntry {
+                to: (group, idx), ..
+            } => (group, Some(idx)),
+        }
+    }
+}
+
+/// Edits done by a single user action, undone and redone together
+#[derive(Debug, Clone, PartialEq)]
+pub struct Change {
+    /// What the action did, e.g. "delete 'ls -la'"
+    pub description: String,
+    pub edits: Vec<Edit>,
+}
+
+impl Change {
+    fn inverse(&self) -> Change {
+        Change {
+            description: self.description.clone(),
+            edits: self.edits.iter().rev().map(Edit::inverse).collect(),
+        }
+    }
+
+    /// Where to put the selection once applied: where its last edit took place,
+    /// or took place again when reverted
+    fn location(&self, reverted: bool) -> Option<(usize, Option<usize>)> {
+        self.edits.last().map(|edit| match reverted {
+            true => edit.inverse().location(),
+            false => edit.location(),
+        })
+    }
+}
+
+/// Undo and redo stacks of the changes done in the session
+#[derive(Debug, Default)]
+pub struct History {
+    undo: Vec<Change>,
+    redo: Vec<Change>,
+}
+
+impl Model {
+    /// Apply the edits of a user action and record them so they can be undone
+    pub fn edit(&mut self, description: String, edits: Vec<Edit>) {
+        if edits.is_empty() {
+            return;
+        }
+        let change = Change { description, edits };
+        self.apply_change(&change, change.location(false));
+
+        self.history.undo.push(change);
+        if self.history.undo.len() > HISTORY_SIZE {
+            self.history.undo.remove(0);
+        }
+        self.history.redo.clear();
+    }
+
+    /// Revert the last change, and tell which one in the status message
+    pub fn undo(&mut self) {
+        self.status = Some(match self.history.undo.pop() {
+            Some(change) => {
+                self.apply_change(&change.inverse(), change.location(true));
+                let status = format!("Undone: {}", change.description);
+                self.history.redo.push(change);
+                status
+            }
+            None => String::from("Nothing to undo"),
+        });
+    }
+
+    /// Apply again the last undone change, and tell which one in the status message
+    pub fn redo(&mut self) {
+        self.status = Some(match self.history.redo.pop() {
+            Some(change) => {
+                self.apply_change(&change, change.location(false));
+                let status = format!("Redone: {}", change.description);
+                self.history.undo.push(change);
+                status
+            }
+            None => String::from("Nothing to redo"),
+        });
+    }
+
+    /// Apply the edits of a change and select the given location
+    fn apply_change(&mut self, change: &Change, location: Option<(usize, Option<usize>)>) {
+        for edit in &change.edits {
+            edit.apply(&mut self.entries);
+        }
+
+        if let Some((idx_entrygroup, idx_entry)) = location {
+            self.idx_entrygroup = idx_entrygroup.min(self.entries.len().saturating_sub(1));
+            if let Some(idx_entry) = idx_entry {
+                let len = self.entries[self.idx_entrygroup].entries.len();
+                self.idx_entry = idx_entry.min(len.saturating_sub(1));
+            } else if self.current_screen == CurrentScreen::Secondary {
+                self.select_first_entry();
+            }
+        }
+
+        // There is nothing to show in the secondary screen for an empty group
+        if self.entries[self.idx_entrygroup].entries.is_empty() {
+            self.current_screen = CurrentScreen::Main;
+            self.zoomed = false;
+        }
+        self.long_info_scroll.reset();
+
+        // Commands may run other programs now
+        self.check_programs();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::tests::make_test_entry_group;
+
+    fn entry(command: &str) -> Entry {
+        Entry {
+            command: String::from(command),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn edits_are_reverted_by_their_inverse() {
+        let mut other = make_test_entry_group();
+        other.description = String::from("other");
+        let edits = vec![
+            Edit::InsertGroup {
+                idx: 1,
+                group: EntryGroup::new(String::from("new"), vec![]),
+            },
+            Edit::DeleteGroup {
+                idx: 0,
+                group: make_test_entry_group(),
+            },
+            Edit::ModifyGroup {
+                idx: 1,
+                before: GroupInfo::of(&other),
+                after: GroupInfo {
+                    description: String::from("renamed"),
+                    pinned: true,
+                },
+            },
+            Edit::MoveGroup {
+                from: 0,
+                len: 1,
+                to: 1,
+            },
+            Edit::InsertEntry {
+                group: 0,
+                idx: 2,
+                entry: entry("ls"),
+            },
+            Edit::DeleteEntry {
+                group: 1,
+                idx: 0,
+                entry: make_test_entry_group().entries[0].clone(),
+            },
+            Edit::ModifyEntry {
+                group: 0,
+                idx: 1,
+                before: make_test_entry_group().entries[1].clone(),
+                after: entry("pwd"),
+            },
+            Edit::MoveEntry {
+                from: (0, 0),
+                to: (1, 2),
+            },
+        ];
+
+        for edit in edits {
+            let original = vec![make_test_entry_group(), other.clone()];
+            let mut entry_groups = original.clone();
+
+            edit.apply(&mut entry_groups);
+            assert_ne!(entry_groups, original, "{:?}", edit);
+            edit.inverse().apply(&mut entry_groups);
+            assert_eq!(entry_groups, original, "{:?}", edit);
+            assert_eq!(edit.inverse().inverse(), edit);
+        }
+    }
+
+    #[test]
+    fn undo_and_redo_changes() {
+        let mut model = Model {
+            entries: vec![make_test_entry_group()],
+            ..Default::default()
+        };
+
+        model.edit(
+            String::from("add 'ls' and 'pwd'"),
+            vec![
+                Edit::InsertEntry {
+                    group: 0,
+                    idx: 2,
+                    entry: entry("ls"),
+                },
+                Edit::MoveEntry {
+                    from: (0, 2),
+                    to: (0, 0),
+                },
+                Edit::InsertEntry {
+                    group: 0,
+                    idx: 1,
+                    entry: entry("pwd"),
+                },
+            ],
+        );
+        let commands = |model: &Model| -> Vec<String> {
+            model.entries[0]
+                .entries
+                .iter()
+                .map(|e| e.command.clone())
+                .collect()
+        };
+        assert_eq!(commands(&model), ["ls", "pwd", "command1", "command2"]);
+        assert_eq!(model.idx_entry, 1);
+
+        model.undo();
+        assert_eq!(commands(&model), ["command1", "command2"]);
+        assert_eq!(model.status.as_deref(), Some("Undone: add 'ls' and 'pwd'"));
+        model.undo();
+        assert_eq!(model.status.as_deref(), Some("Nothing to undo"));
+
+        model.redo();
+        assert_eq!(commands(&model), ["ls", "pwd", "command1", "command2"]);
+        assert_eq!(model.status.as_deref(), Some("Redone: add 'ls' and 'pwd'"));
+
+        // A new change drops the undone ones
+        model.undo();
+        model.edit(
+            String::from("delete 'command1'"),
+            vec![Edit::DeleteEntry {
+                group: 0,
+                idx: 0,
+                entry: model.entries[0].entries[0].clone(),
+            }],
+        );
+        model.redo();
+        assert_eq!(model.status.as_deref(), Some("Nothing to redo"));
+        assert_eq!(commands(&model), ["command2"]);
+    }
+
+    #[test]
+    fn history_is_bounded() {
+        let mut model = Model {
+            entries: vec![make_test_entry_group()],
+            ..Default::default()
+        };
+        for i in 0..HISTORY_SIZE + 5 {
+            model.edit(
+                format!("move {}", i),
+                vec![Edit::MoveEntry {
+                    from: (0, 0),
+                    to: (0, 1),
+                }],
+            );
+        }
+        assert_eq!(model.history.undo.len(), HISTORY_SIZE);
+        assert_eq!(model.history.undo[0].description, "move 5");
+    }
+}
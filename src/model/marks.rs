@@ -0,0 +1,138 @@
+//! Entries marked to be copied together
+
+use crate::model::Model;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// How the marked commands are joined when copied
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Separator {
+    /// One command per line, as a script
+    #[default]
+    Newline,
+    /// Stop at the first failing command
+    And,
+    /// Run every command
+    Semicolon,
+}
+
+impl Separator {
+    pub fn next(self) -> Separator {
+        match self {
+            Separator::Newline => Separator::And,
+            Separator::And => Separator::Semicolon,
+            Separator::Semicolon => Separator::Newline,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Separator::Newline => "\n",
+            Separator::And => " && ",
+            Separator::Semicolon => "; ",
+        }
+    }
+}
+
+impl fmt::Display for Separator {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Separator::Newline => "newlines",
+            Separator::And => "&&",
+            Separator::Semicolon => ";",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl Model {
+    /// Mark or unmark the selected entry. Marks are kept when going to other groups
+    pub fn toggle_mark(&mut self) {
+        let command = &self.entries[self.idx_entrygroup].entries[self.idx_entry].command;
+        match self.marked.iter().position(|c| c == command) {
+            Some(pos) => {
+                self.marked.remove(pos);
+            }
+            None => self.marked.push(command.clone()),
+        }
+    }
+
+    pub fn clear_marks(&mut self) {
+        self.marked.clear();
+    }
+
+    /// Position of the command in the marked ones, starting at 1, if marked
+    pub fn mark_of(&self, command: &str) -> Option<usize> {
+        self.marked.iter().position(|c| c == command).map(|i| i + 1)
+    }
+
+    /// Switch to the next way of joining the marked commands
+    pub fn cycle_separator(&mut self) {
+        self.separator = self.separator.next();
+        self.status = Some(format!("Marked commands joined with {}", self.separator));
+    }
+
+    /// Commands copied when leaving: the marked ones in the order they were marked,
+    /// or else the selected one
+    pub fn copied_commands(&self) -> Vec<&str> {
+        match self.marked.is_empty() {
+            true => self
+                .entries
+                .get(self.idx_entrygroup)
+                .and_then(|entry_group| entry_group.entries.get(self.idx_entry))
+                .map(|entry| vec![entry.command.as_str()])
+                .unwrap_or_default(),
+            false => self.marked.iter().map(String::as_str).collect(),
+        }
+    }
+
+    /// Text copied when leaving: the copied commands joined with the separator
+    pub fn copied_text(&self) -> Option<String> {
+        let commands = self.copied_commands();
+        (!commands.is_empty()).then(|| commands.join(self.separator.as_str()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::tests::make_test_entry_group;
+    use crate::model::CurrentScreen;
+
+    #[test]
+    fn mark_entries_across_groups() {
+        let mut model = Model {
+            entries: vec![make_test_entry_group(), make_test_entry_group()],
+            current_screen: CurrentScreen::Secondary,
+            idx_entry: 1,
+            ..Default::default()
+        };
+        model.entries[1].entries[0].command = String::from("ls");
+        assert_eq!(model.copied_text().as_deref(), Some("command2"));
+
+        model.toggle_mark();
+        model.idx_entrygroup = 1;
+        model.idx_entry = 0;
+        model.toggle_mark();
+        assert_eq!(model.mark_of("ls"), Some(2));
+        assert_eq!(model.copied_text().as_deref(), Some("command2\nls"));
+
+        model.cycle_separator();
+        assert_eq!(model.copied_text().as_deref(), Some("command2 && ls"));
+        model.cycle_separator();
+        assert_eq!(model.copied_text().as_deref(), Some("command2; ls"));
+
+        // Unmarking keeps the order of the others
+        model.idx_entrygroup = 0;
+        model.idx_entry = 0;
+        model.toggle_mark();
+        model.idx_entry = 1;
+        model.toggle_mark();
+        assert_eq!(model.copied_text().as_deref(), Some("ls; command1"));
+
+        model.clear_marks();
+        assert_eq!(model.mark_of("ls"), None);
+        assert_eq!(model.copied_text().as_deref(), Some("command2"));
+    }
+}
@@ -1,7 +1,34 @@
-pub mod entry;
-mod model_io;
+//! State of the application: the entry groups of the database, the usage statistics
+//! and what is selected and shown
 
-use crate::entry::EntryGroup;
+pub mod crypto;
+pub mod danger;
+pub mod documentation;
+pub mod entry;
+pub mod history;
+pub mod marks;
+pub mod model_io;
+mod pinned;
+pub mod programs;
+mod project_scan;
+pub mod prompt;
+mod reorder;
+pub mod secrets;
+mod tree;
+pub mod usage;
+
+use crate::config::Config;
+use crate::entry::{Entry, EntryGroup};
+use crypto::{SecretKey, SALT_LEN};
+use danger::{Confirmation, DangerRules};
+use history::History;
+use marks::Separator;
+use prompt::Prompt;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::ffi::OsString;
+use std::path::PathBuf;
+use usage::{SortMode, UsageStats};
 
 /// Model's current running state
 #[derive(Debug, Default, PartialEq, Eq)]
@@ -20,16 +47,128 @@ pub enum CurrentScreen {
     Secondary,
 }
 
+/// Panel that receives the navigation keys
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Panel {
+    /// List of entry groups or entries
+    #[default]
+    Entries,
+    /// Long description of the selected entry, scrolled line by line
+    Description,
+}
+
+/// Scroll state of a text panel. Its bounds are only known once it has been rendered
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Scroll {
+    /// First line shown
+    pub offset: u16,
+    /// Last valid offset, i.e. the number of lines that do not fit in the panel
+    pub max: u16,
+    /// Number of lines shown at once
+    pub page: u16,
+}
+
+impl Scroll {
+    /// Scroll one page down, stopping at the end of the text
+    pub fn page_down(&mut self) {
+        self.offset = self.offset.saturating_add(self.page.max(1)).min(self.max);
+    }
+
+    /// Scroll one page up, stopping at the beginning of the text
+    pub fn page_up(&mut self) {
+        self.offset = self.offset.saturating_sub(self.page.max(1));
+    }
+
+    /// Scroll `lines` lines down (positive) or up (negative) within the text
+    pub fn scroll_lines(&mut self, lines: i16) {
+        self.offset = self.offset.saturating_add_signed(lines).min(self.max);
+    }
+
+    /// Go back to the beginning of the text
+    pub fn reset(&mut self) {
+        self.offset = 0;
+    }
+}
+
 /// Flagship struct in the package. Contains all needed information to display the correct
 /// behaviour
 #[derive(Debug, Default)]
 pub struct Model {
     pub file: String,
-    pub(crate) entries: Vec<EntryGroup>,
+    /// Entry groups as a flat list, every group being followed by its descendants
+    pub entries: Vec<EntryGroup>,
     pub running_state: RunningState,
     pub idx_entrygroup: usize,
     pub idx_entry: usize,
     pub current_screen: CurrentScreen,
+    /// Directory scanned for project tasks (Makefile, justfile...) when loading
+    pub project_dir: Option<PathBuf>,
+    /// Usage statistics indexed by command
+    pub stats: HashMap<String, UsageStats>,
+    /// Order of the entry groups in the main screen
+    pub group_sort: SortMode,
+    /// Order of the entries in the secondary screen
+    pub entry_sort: SortMode,
+    /// Whether the first entry group is the virtual "Recent" one
+    pub has_recent_group: bool,
+    /// User configuration
+    pub config: Config,
+    /// Scroll state of the long description panel
+    pub long_info_scroll: Scroll,
+    /// Whether long commands are wrapped instead of truncated in the entry list
+    pub wrap_commands: bool,
+    /// Whether the selected entry is shown full-screen
+    pub zoomed: bool,
+    /// Panel that receives the navigation keys
+    pub focus: Panel,
+    /// Whether the help popup is shown over the current screen
+    pub show_help: bool,
+    /// Scroll state of the help popup
+    pub help_scroll: Scroll,
+    /// Whether the recent log messages are shown over the current screen
+    pub show_log: bool,
+    /// Changes that can be undone and redone
+    pub history: History,
+    /// Message shown in place of the keybindings until the next key press
+    pub status: Option<String>,
+    /// Error that stopped the application, reported once it has quit
+    pub failure: Option<String>,
+    /// Whether nothing is written when quitting, e.g. when replaying a session
+    pub dry_run: bool,
+    /// Whether the documentation of the selected command is to be read, once the status
+    /// telling so is shown
+    pub reading_documentation: bool,
+    /// Last cut entry and where it was, to be moved in another group when pasted. It stays
+    /// in place until then
+    pub cut: Option<((usize, usize), Entry)>,
+    /// Commands marked to be copied together, in the order they were marked
+    pub marked: Vec<String>,
+    /// How the marked commands are joined
+    pub separator: Separator,
+    /// Whether the screens use the compact layouts of the inline mode
+    pub compact: bool,
+    /// Rules flagging the commands that need a confirmation to be copied
+    pub danger: DangerRules,
+    /// Copy of dangerous commands waiting to be confirmed
+    pub confirmation: Confirmation,
+    /// Key of the secret values, once the passphrase was typed
+    pub secret_key: Option<SecretKey>,
+    /// Whether the secret values are shown instead of masked
+    pub reveal_secrets: bool,
+    /// Key of the database file, if encrypted
+    pub database_key: Option<SecretKey>,
+    /// JSON text of the database when last loaded or saved, and the salt of its key, so that
+    /// it is only written again once changed
+    pub saved: Option<(String, Option<[u8; SALT_LEN]>)>,
+    /// Directories searched for the programs of the commands, like $PATH. None to not
+    /// look them up
+    pub search_path: Option<OsString>,
+    /// Programs of the commands that are not installed
+    pub missing_programs: HashSet<String>,
+    /// Whether the entries whose program is not installed are hidden
+    pub hide_missing: bool,
+    /// Text being typed in a popup, if any
+    pub prompt: Option<Prompt>,
 }
 
 impl Model {
@@ -42,6 +181,48 @@ impl Model {
             idx_entrygroup: 0,
             idx_entry: 0,
             current_screen: CurrentScreen::Main,
+            project_dir: None,
+            stats: HashMap::new(),
+            group_sort: SortMode::Manual,
+            entry_sort: SortMode::Manual,
+            has_recent_group: false,
+            config: Config::default(),
+            long_info_scroll: Scroll::default(),
+            wrap_commands: false,
+            zoomed: false,
+            focus: Panel::Entries,
+            show_help: false,
+            help_scroll: Scroll::default(),
+            show_log: false,
+            history: History::default(),
+            status: None,
+            failure: None,
+            dry_run: false,
+            reading_documentation: false,
+            cut: None,
+            marked: vec![],
+            separator: Separator::default(),
+            compact: false,
+            danger: DangerRules::new(&Config::default().dangerous_patterns)
+                .expect("Invalid default dangerous patterns"),
+            confirmation: Confirmation::default(),
+            secret_key: None,
+            reveal_secrets: false,
+            database_key: None,
+            saved: None,
+            search_path: std::env::var_os("PATH"),
+            missing_programs: HashSet::new(),
+            hide_missing: false,
+            prompt: None,
+        }
+    }
+
+    /// Scroll state moved by the scroll actions: the help popup if shown,
+    /// else the long description
+    pub fn active_scroll(&mut self) -> &mut Scroll {
+        match self.show_help {
+            true => &mut self.help_scroll,
+            false => &mut self.long_info_scroll,
         }
     }
 
@@ -49,11 +230,21 @@ impl Model {
     pub fn next_entry(&mut self) {
         match self.current_screen {
             CurrentScreen::Main => {
-                self.idx_entrygroup = (self.idx_entrygroup + 1) % self.entries.len();
+                let order = self.visible_group_order();
+                if order.is_empty() {
+                    return;
+                }
+                let pos = position(&order, self.idx_entrygroup);
+                self.idx_entrygroup = order[(pos + 1) % order.len()];
             }
             CurrentScreen::Secondary => {
-                self.idx_entry =
-                    (self.idx_entry + 1) % self.entries[self.idx_entrygroup].entries.len();
+                let order = self.entry_order();
+                if order.is_empty() {
+                    return;
+                }
+                let pos = position(&order, self.idx_entry);
+                self.idx_entry = order[(pos + 1) % order.len()];
+                self.long_info_scroll.reset();
             }
         }
     }
@@ -62,19 +253,41 @@ impl Model {
     pub fn previous_entry(&mut self) {
         match self.current_screen {
             CurrentScreen::Main => {
-                self.idx_entrygroup = match self.idx_entrygroup {
-                    0 => self.entries.len() - 1,
-                    _ => self.idx_entrygroup - 1,
+                let order = self.visible_group_order();
+                if order.is_empty() {
+                    return;
+                }
+                let pos = match position(&order, self.idx_entrygroup) {
+                    0 => order.len() - 1,
+                    pos => pos - 1,
                 };
+                self.idx_entrygroup = order[pos];
             }
             CurrentScreen::Secondary => {
-                self.idx_entry = match self.idx_entry {
-                    0 => self.entries[self.idx_entrygroup].entries.len() - 1,
-                    _ => self.idx_entry - 1,
+                let order = self.entry_order();
+                if order.is_empty() {
+                    return;
+                }
+                let pos = match position(&order, self.idx_entry) {
+                    0 => order.len() - 1,
+                    pos => pos - 1,
                 };
+                self.idx_entry = order[pos];
+                self.long_info_scroll.reset();
             }
         }
     }
+
+    /// Highlight the first entry shown for the selected entry group
+    pub fn select_first_entry(&mut self) {
+        self.idx_entry = self.entry_order().first().copied().unwrap_or(0);
+        self.long_info_scroll.reset();
+    }
+}
+
+/// Position of `idx` in the shown `order`
+pub fn position(order: &[usize], idx: usize) -> usize {
+    order.iter().position(|&i| i == idx).unwrap_or(0)
 }
 
 #[cfg(test)]
@@ -87,12 +300,14 @@ pub mod tests {
             command: String::from("command1"),
             short_info: String::from("Short description 1"),
             long_info: String::from("Long description 1"),
+            ..Default::default()
         };
 
         let entry2 = Entry {
             command: String::from("command2"),
             short_info: String::from("Short description 2"),
             long_info: String::from("Long description 2"),
+            ..Default::default()
         };
 
         let description = String::from("description");
@@ -111,6 +326,14 @@ pub mod tests {
         assert_eq!(model.idx_entry, 0);
     }
 
+    #[test]
+    fn move_without_groups() {
+        let mut model = Model::default();
+        model.next_entry();
+        model.previous_entry();
+        assert_eq!(model.idx_entrygroup, 0);
+    }
+
     #[test]
     fn create_model() {
         let file = String::from("test.cache");
@@ -138,6 +361,7 @@ pub mod tests {
                 command: "".to_owned(),
                 short_info: "".to_owned(),
                 long_info: "".to_owned(),
+                ..Default::default()
             };
             let eg = EntryGroup::new("".to_owned(), vec![entry]);
 
@@ -173,6 +397,7 @@ pub mod tests {
                 command: "".to_owned(),
                 short_info: "".to_owned(),
                 long_info: "".to_owned(),
+                ..Default::default()
             };
             let eg = EntryGroup::new("".to_owned(), vec![entry]);
 
@@ -208,6 +433,7 @@ pub mod tests {
                 command: "".to_owned(),
                 short_info: "".to_owned(),
                 long_info: "".to_owned(),
+                ..Default::default()
             };
 
             entries.push(entry);
@@ -248,6 +474,7 @@ pub mod tests {
                 command: "".to_owned(),
                 short_info: "".to_owned(),
                 long_info: "".to_owned(),
+                ..Default::default()
             };
 
             entries.push(entry);
@@ -277,4 +504,49 @@ pub mod tests {
         model.previous_entry();
         assert_eq!(model.idx_entry, 0);
     }
+
+    #[test]
+    fn scroll_long_info() {
+        let mut scroll = Scroll {
+            offset: 0,
+            max: 25,
+            page: 10,
+        };
+
+        scroll.page_down();
+        assert_eq!(scroll.offset, 10);
+        scroll.page_down();
+        scroll.page_down();
+        assert_eq!(scroll.offset, 25);
+        scroll.page_up();
+        assert_eq!(scroll.offset, 15);
+        scroll.page_up();
+        scroll.page_up();
+        assert_eq!(scroll.offset, 0);
+
+        scroll.scroll_lines(3);
+        assert_eq!(scroll.offset, 3);
+        scroll.scroll_lines(-1);
+        assert_eq!(scroll.offset, 2);
+        scroll.scroll_lines(-5);
+        assert_eq!(scroll.offset, 0);
+        scroll.scroll_lines(30);
+        assert_eq!(scroll.offset, 25);
+
+        scroll.reset();
+        assert_eq!(scroll.offset, 0);
+    }
+
+    #[test]
+    fn scroll_is_reset_when_selection_changes() {
+        let mut model = Model {
+            entries: vec![make_test_entry_group()],
+            current_screen: CurrentScreen::Secondary,
+            ..Default::default()
+        };
+        model.long_info_scroll.offset = 3;
+
+        model.next_entry();
+        assert_eq!(model.long_info_scroll.offset, 0);
+    }
 }
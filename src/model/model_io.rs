@@ -1,69 +1,308 @@
+//! Reading and writing the database file, a JSON tree of entry groups. The file can also be
+//! an encrypted container of the JSON text, for a passphrase-derived key
+
 use crate::entry::EntryGroup;
-use crate::model::Model;
-use crate::RunningState;
+use crate::model::crypto::{salt_of, CryptoError, SecretKey, SALT_LEN};
+use crate::model::prompt::{Prompt, PromptKind};
+use crate::model::tree::{flatten, unflatten};
+use crate::model::{Model, RunningState};
 use std::fs::create_dir_all;
 use std::fs::File;
 use std::io::prelude::*;
-use std::io::Write;
-use std::path::Path;
+use std::io::{self, ErrorKind, Write};
+use std::path::{Path, PathBuf};
+
+/// First bytes of the encrypted files, followed by the salt of the key, the nonce and the
+/// encrypted JSON text
+const ENCRYPTED_HEADER: &[u8] = b"memors-encrypted-v1\n";
+
+/// Whether the file is encrypted. Missing files are not
+pub fn is_encrypted(path: &Path) -> io::Result<bool> {
+    let mut header = vec![];
+    match File::open(path) {
+        Ok(file) => file
+            .take(ENCRYPTED_HEADER.len() as u64)
+            .read_to_end(&mut header)?,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(false),
+        Err(e) => return Err(e),
+    };
+    Ok(header == ENCRYPTED_HEADER)
+}
+
+/// Derive the key of an encrypted file from its passphrase. A wrong passphrase gives a
+/// `PermissionDenied` error
+pub fn file_key(path: &Path, passphrase: &str) -> io::Result<SecretKey> {
+    let data = std::fs::read(path)?;
+    let encrypted = data
+        .strip_prefix(ENCRYPTED_HEADER)
+        .ok_or_else(|| io::Error::new(ErrorKind::InvalidData, "the file is not encrypted"))?;
+    let key = SecretKey::derive(passphrase, salt_of(encrypted).map_err(crypto_error)?);
+    key.decrypt(encrypted).map_err(crypto_error)?;
+    Ok(key)
+}
+
+fn crypto_error(e: CryptoError) -> io::Error {
+    match e {
+        CryptoError::Malformed => io::Error::new(ErrorKind::InvalidData, e),
+        CryptoError::Decryption => io::Error::new(ErrorKind::PermissionDenied, e),
+    }
+}
+
+/// Read a text file, decrypting it with the key if encrypted. Encrypted files cannot be
+/// read without it
+fn read_text(path: &Path, key: Option<&SecretKey>) -> io::Result<String> {
+    let data = std::fs::read(path)?;
+    let data = match data.strip_prefix(ENCRYPTED_HEADER) {
+        Some(encrypted) => match key {
+            Some(key) => key.decrypt(encrypted).map_err(crypto_error)?,
+            None => {
+                return Err(io::Error::new(
+                    ErrorKind::PermissionDenied,
+                    "the file is encrypted",
+                ))
+            }
+        },
+        None => data,
+    };
+    String::from_utf8(data).map_err(|e| io::Error::new(ErrorKind::InvalidData, e))
+}
+
+/// Write a text file, encrypted with the key if any, creating its parent directories
+/// if needed. The text is written to a temporary file next to it first, renamed once
+/// complete, so that a failed write leaves the previous file intact. The file keeps its
+/// permissions, and new files are only readable by their owner
+fn write_text(path: &Path, text: &str, key: Option<&SecretKey>) -> io::Result<()> {
+    // Ensure the parent directories exist
+    if let Some(parent_dir) = path.parent() {
+        create_dir_all(parent_dir)?;
+    }
+
+    let name = path
+        .file_name()
+        .ok_or_else(|| io::Error::new(ErrorKind::InvalidInput, "not a file path"))?;
+    let temp_path = path.with_file_name(format!(".{}.tmp", name.to_string_lossy()));
+    let written = File::create(&temp_path).and_then(|mut file| {
+        match key {
+            Some(key) => {
+                file.write_all(ENCRYPTED_HEADER)?;
+                file.write_all(&key.encrypt(text.as_bytes()))?;
+            }
+            None => file.write_all(text.as_bytes())?,
+        }
+        match std::fs::metadata(path) {
+            Ok(metadata) => file.set_permissions(metadata.permissions())?,
+            Err(e) if e.kind() == ErrorKind::NotFound => set_private(&file)?,
+            Err(e) => return Err(e),
+        }
+        file.sync_all()
+    });
+    match written.and_then(|()| std::fs::rename(&temp_path, path)) {
+        Ok(()) => Ok(()),
+        Err(e) => {
+            let _ = std::fs::remove_file(&temp_path);
+            Err(e)
+        }
+    }
+}
+
+/// Make the file only readable and writable by its owner
+#[cfg(unix)]
+fn set_private(file: &File) -> io::Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+    file.set_permissions(std::fs::Permissions::from_mode(0o600))
+}
+
+#[cfg(not(unix))]
+fn set_private(_file: &File) -> io::Result<()> {
+    Ok(())
+}
+
+/// Read the entry groups of a database file, child groups being nested in their parent
+pub fn load_entry_groups(path: &Path) -> io::Result<Vec<EntryGroup>> {
+    load_encrypted_entry_groups(path, None)
+}
+
+/// Read the entry groups of a database file, decrypting it with the key if encrypted
+pub fn load_encrypted_entry_groups(
+    path: &Path,
+    key: Option<&SecretKey>,
+) -> io::Result<Vec<EntryGroup>> {
+    let json_data = read_text(path, key)?;
+
+    serde_json::from_str(&json_data).map_err(|e| io::Error::new(ErrorKind::InvalidData, e))
+}
+
+/// Write entry groups to a database file, creating its parent directories if needed
+pub fn save_entry_groups(path: &Path, entry_groups: &[EntryGroup]) -> io::Result<()> {
+    save_encrypted_entry_groups(path, entry_groups, None)
+}
+
+/// Write entry groups to a database file, encrypted with the key if any
+pub fn save_encrypted_entry_groups(
+    path: &Path,
+    entry_groups: &[EntryGroup],
+    key: Option<&SecretKey>,
+) -> io::Result<()> {
+    let json_data = serde_json::to_string_pretty(entry_groups)?;
+
+    write_text(path, &json_data, key)
+}
 
 impl Model {
-    /// Load model state from cache file
-    pub fn load_from_cache(&mut self) {
+    /// Load model state from cache file. Encrypted files are only loaded once their
+    /// passphrase was typed: until then, it is asked, nothing is loaded and false is returned.
+    /// A missing file starts an empty database, other read errors are returned
+    pub fn load_from_cache(&mut self) -> io::Result<bool> {
         if self.file.is_empty() {
-            panic!("Trying to read previous status, but no path was specified");
+            return Err(io::Error::new(
+                ErrorKind::InvalidInput,
+                "no database file was specified",
+            ));
+        }
+
+        let path = Path::new(&self.file);
+        if self.database_key.is_none() && is_encrypted(path)? {
+            tracing::info!(file = %self.file, "encrypted database, asking its passphrase");
+            self.prompt = Some(Prompt::new(PromptKind::DatabasePassphrase));
+            self.running_state = RunningState::Loaded;
+            return Ok(false);
         }
 
-        // Attempt to open the file
-        let file_result = File::open(&self.file);
-
-        // Check if the file was successfully opened
-        match file_result {
-            Ok(mut file) => {
-                // File exists, read JSON data from the file
-                let mut json_data = String::new();
-                file.read_to_string(&mut json_data)
-                    .expect("Failed to read cache file");
-
-                // Deserialize JSON data into a vector of Entry structs
-                let entry_groups: Vec<EntryGroup> =
-                    serde_json::from_str(&json_data).expect("Deserialization failed");
-
-                // Print loaded entries for demonstration
-                for entry_group in entry_groups {
-                    self.entries.push(entry_group);
-                }
+        match load_encrypted_entry_groups(path, self.database_key.as_ref()) {
+            Ok(entry_groups) => {
+                // The model keeps the tree of groups as a flat list
+                self.entries.extend(flatten(entry_groups));
+                self.saved = Some(self.database_state()?);
+                tracing::info!(file = %self.file, groups = self.entries.len(), "loaded the database");
             }
-            Err(_) => {
-                // File does not exist, do nothing
-                // TODO: automate cache file?
+            Err(e) if e.kind() == ErrorKind::NotFound => {
+                tracing::warn!(file = %self.file, "no database yet, starting empty");
             }
+            Err(e) => return Err(e),
         }
 
+        self.load_stats();
+
         self.running_state = RunningState::Loaded;
+        Ok(true)
+    }
+
+    /// Unlock the encrypted database with its passphrase, to load it
+    pub fn unlock_database(&mut self, passphrase: &str) -> bool {
+        match file_key(Path::new(&self.file), passphrase) {
+            Ok(key) => {
+                self.database_key = Some(key);
+                true
+            }
+            Err(e) => {
+                tracing::warn!(file = %self.file, "cannot unlock the database: {}", e);
+                false
+            }
+        }
+    }
+
+    /// Ask the passphrase the database is encrypted with from now on
+    pub fn start_encrypting_database(&mut self) {
+        self.prompt = Some(Prompt::new(PromptKind::NewDatabasePassphrase));
+    }
+
+    /// Encrypt the database with a new passphrase when saved, or store it in plain text if
+    /// empty
+    pub(crate) fn encrypt_database(&mut self, passphrase: &str) {
+        self.database_key = (!passphrase.is_empty()).then(|| SecretKey::new(passphrase));
+        self.status = Some(String::from(match self.database_key {
+            Some(_) => "The database will be saved encrypted",
+            None => "The database will be saved in plain text",
+        }));
+    }
+
+    /// Path of the usage statistics file, stored next to the cache file
+    pub fn stats_file(&self) -> PathBuf {
+        Path::new(&self.file).with_extension("stats.json")
     }
 
-    /// Load model state to cache file
-    pub fn save_to_cache(&mut self) {
+    /// Load usage statistics from the stats file, if any. It is encrypted like the database.
+    /// A corrupt file is only worth a warning, as the statistics are gathered again
+    fn load_stats(&mut self) {
+        let Ok(json_data) = read_text(&self.stats_file(), self.database_key.as_ref()) else {
+            return;
+        };
+        match serde_json::from_str(&json_data) {
+            Ok(stats) => {
+                self.stats = stats;
+                tracing::debug!(commands = self.stats.len(), "loaded the usage statistics");
+            }
+            Err(e) => {
+                tracing::warn!("invalid usage statistics, starting over: {}", e);
+                self.stats.clear();
+            }
+        }
+    }
+
+    /// Save usage statistics to the stats file
+    fn save_stats(&self) -> io::Result<()> {
+        if self.stats.is_empty() {
+            return Ok(());
+        }
+
+        let json_data = serde_json::to_string_pretty(&self.stats)?;
+
+        write_text(&self.stats_file(), &json_data, self.database_key.as_ref())
+    }
+
+    /// Stored entry groups as a tree, skipping the virtual ones, in JSON, and the salt of the
+    /// key they are encrypted with
+    fn database_state(&self) -> io::Result<(String, Option<[u8; SALT_LEN]>)> {
+        let stored_entries: Vec<EntryGroup> = unflatten(
+            self.entries
+                .iter()
+                .filter(|eg| !eg.read_only)
+                .cloned()
+                .collect(),
+        );
+        let json_data = serde_json::to_string_pretty(&stored_entries)?;
+        Ok((json_data, self.database_key.as_ref().map(|key| key.salt)))
+    }
+
+    /// Save the usage statistics, and the database if it changed since it was loaded, then
+    /// quit. Nothing is written over a database file that was not loaded, e.g. when its
+    /// passphrase was not typed, as its entries would be lost
+    pub fn save_to_cache(&mut self) -> io::Result<()> {
         if self.file.is_empty() {
-            panic!("Trying to save current status, but no path was specified");
+            return Err(io::Error::new(
+                ErrorKind::InvalidInput,
+                "no database file was specified",
+            ));
         }
 
-        // Serialize EntryGroups to JSON
-        let json_data = serde_json::to_string_pretty(&self.entries).expect("Serialization failed");
+        if self.dry_run {
+            tracing::info!(file = %self.file, "dry run, nothing saved");
+            self.running_state = RunningState::Done;
+            return Ok(());
+        }
 
-        // Ensure the parent directories exist
-        let parent_dir = Path::new(&self.file).parent().unwrap();
-        if !parent_dir.exists() {
-            create_dir_all(parent_dir).expect("Failed to create directories");
+        // Only loaded databases have a saved state, or new ones whose file is still missing
+        if self.saved.is_none() && !matches!(Path::new(&self.file).try_exists(), Ok(false)) {
+            tracing::warn!(file = %self.file, "the database was not loaded, nothing saved");
+            self.running_state = RunningState::Done;
+            return Ok(());
         }
 
-        let mut file = File::create(&self.file).expect("Failed to create the cache file");
+        let state = self.database_state()?;
+        if self.saved.as_ref() != Some(&state) {
+            write_text(Path::new(&self.file), &state.0, self.database_key.as_ref())?;
+            tracing::info!(
+                file = %self.file,
+                encrypted = self.database_key.is_some(),
+                "saved the database"
+            );
+            self.saved = Some(state);
+        }
 
-        file.write_all(json_data.as_bytes())
-            .expect("Failed to write to file");
+        self.save_stats()?;
 
         self.running_state = RunningState::Done;
+        Ok(())
     }
 }
 
@@ -79,12 +318,12 @@ mod tests {
         let mut model = Model::new(String::from("./path/to/test/test.cache"));
         model.entries = vec![entrygroup];
 
-        model.save_to_cache();
+        model.save_to_cache()?;
 
         model.entries.clear();
         model.running_state = RunningState::Empty;
 
-        model.load_from_cache();
+        model.load_from_cache()?;
 
         assert_eq!(model.file.len(), 25);
         assert_eq!(model.entries.len(), 1);
@@ -92,8 +331,214 @@ mod tests {
         assert_eq!(model.running_state, RunningState::Loaded);
         assert_eq!(model.entries[0].entries[0].command, "command1");
 
+        // Invalid files and missing paths are errors, left to the caller
+        std::fs::write(&model.file, "not json")?;
+        let error = model.load_from_cache().unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::InvalidData);
+        let error = Model::new(String::new()).save_to_cache().unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::InvalidInput);
+
+        // Dry runs write nothing
+        model.dry_run = true;
+        model.save_to_cache()?;
+        assert_eq!(std::fs::read_to_string(&model.file)?, "not json");
+        assert_eq!(model.running_state, RunningState::Done);
+
+        // Nor do models whose existing file could not be loaded
+        let mut model = Model::new(model.file.clone());
+        assert!(model.load_from_cache().is_err());
+        model.entries = vec![make_test_entry_group()];
+        model.save_to_cache()?;
+        assert_eq!(std::fs::read_to_string(&model.file)?, "not json");
+        assert_eq!(model.running_state, RunningState::Done);
+
+        // Other read errors than a missing file are returned
+        std::fs::remove_file(&model.file)?;
+        std::fs::create_dir(&model.file)?;
+        assert!(Model::new(model.file.clone()).load_from_cache().is_err());
+        std::fs::remove_dir(&model.file)?;
+        let mut model = Model::new(model.file.clone());
+        assert!(model.load_from_cache()?);
+        assert!(model.entries.is_empty());
+
+        // Writes go through a temporary file, removed once renamed
+        model.entries = vec![make_test_entry_group()];
+        model.save_to_cache()?;
+        let names: Vec<_> = std::fs::read_dir("./path/to/test")?
+            .map(|entry| entry.map(|entry| entry.file_name()))
+            .collect::<io::Result<_>>()?;
+        assert_eq!(names, ["test.cache"]);
+
         // Cleaning
         std::fs::remove_dir_all("./path")?;
         Ok(())
     }
+
+    #[cfg(unix)]
+    #[test]
+    fn keep_the_permissions() -> std::io::Result<()> {
+        use std::os::unix::fs::PermissionsExt;
+        let mode =
+            |path: &Path| -> io::Result<u32> { Ok(path.metadata()?.permissions().mode() & 0o777) };
+
+        let path = Path::new("./path_permissions/test.json");
+        let entry_groups = vec![make_test_entry_group()];
+        save_entry_groups(path, &entry_groups)?;
+        assert_eq!(mode(path)?, 0o600);
+
+        std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o640))?;
+        save_entry_groups(path, &entry_groups)?;
+        assert_eq!(mode(path)?, 0o640);
+
+        // Cleaning
+        std::fs::remove_dir_all("./path_permissions")?;
+        Ok(())
+    }
+
+    #[test]
+    fn save_and_load_entry_groups() -> std::io::Result<()> {
+        let path = Path::new("./path_groups/nested/test.json");
+        let mut entry_groups = vec![make_test_entry_group()];
+        entry_groups[0].groups.push(make_test_entry_group());
+
+        save_entry_groups(path, &entry_groups)?;
+        assert_eq!(load_entry_groups(path)?, entry_groups);
+
+        std::fs::write(path, "not json")?;
+        let error = load_entry_groups(path).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::InvalidData);
+
+        // Cleaning
+        std::fs::remove_dir_all("./path_groups")?;
+        Ok(())
+    }
+
+    #[test]
+    fn save_and_load_stats() -> std::io::Result<()> {
+        let mut model = Model::new(String::from("./path_stats/test.cache"));
+        model.entries = vec![make_test_entry_group()];
+        model.record_usage();
+
+        model.save_to_cache()?;
+        assert!(Path::new("./path_stats/test.stats.json").exists());
+
+        model.entries.clear();
+        model.stats.clear();
+        model.load_from_cache()?;
+
+        assert_eq!(model.stats.len(), 1);
+        assert_eq!(model.stats_of("command1").count, 1);
+
+        // Only the statistics are written when the entries did not change
+        std::fs::write(&model.file, "[]")?;
+        model.record_usage();
+        model.save_to_cache()?;
+        assert_eq!(std::fs::read_to_string(&model.file)?, "[]");
+        assert_eq!(model.stats_of("command1").count, 2);
+        model.entries[0].entries[0].command = String::from("changed");
+        model.save_to_cache()?;
+        assert!(std::fs::read_to_string(&model.file)?.contains("changed"));
+
+        // A corrupt file is ignored
+        std::fs::write(model.stats_file(), "{\"command1\": {\"cou")?;
+        model.entries.clear();
+        model.load_from_cache()?;
+        assert!(model.stats.is_empty());
+        assert_eq!(model.entries.len(), 1);
+
+        // Cleaning
+        std::fs::remove_dir_all("./path_stats")?;
+        Ok(())
+    }
+
+    #[test]
+    fn save_and_load_encrypted_entry_groups() -> std::io::Result<()> {
+        let path = Path::new("./path_encrypted/test.json");
+        let entry_groups = vec![make_test_entry_group()];
+        let key = SecretKey::new("correct horse");
+
+        save_encrypted_entry_groups(path, &entry_groups, Some(&key))?;
+        assert!(is_encrypted(path)?);
+        assert!(!std::fs::read(path)?
+            .windows("command1".len())
+            .any(|bytes| bytes == b"command1"));
+
+        // The key is derived again from the passphrase
+        let key = file_key(path, "correct horse")?;
+        assert_eq!(load_encrypted_entry_groups(path, Some(&key))?, entry_groups);
+        let error = file_key(path, "battery staple").unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::PermissionDenied);
+        let error = load_entry_groups(path).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::PermissionDenied);
+
+        // Plain files are read with or without a key
+        save_entry_groups(path, &entry_groups)?;
+        assert!(!is_encrypted(path)?);
+        assert_eq!(load_encrypted_entry_groups(path, Some(&key))?, entry_groups);
+        assert!(!is_encrypted(Path::new("./path_encrypted/missing.json"))?);
+
+        // Cleaning
+        std::fs::remove_dir_all("./path_encrypted")?;
+        Ok(())
+    }
+
+    #[test]
+    fn encrypted_database_is_loaded_once_unlocked() -> std::io::Result<()> {
+        let mut model = Model::new(String::from("./path_unlock/test.cache"));
+        model.entries = vec![make_test_entry_group()];
+        model.record_usage();
+        model.encrypt_database("correct horse");
+        model.save_to_cache()?;
+        assert!(is_encrypted(&model.stats_file())?);
+
+        let mut model = Model::new(String::from("./path_unlock/test.cache"));
+        assert!(!model.load_from_cache()?);
+        assert!(model.entries.is_empty());
+        assert_eq!(
+            model.prompt.as_ref().map(|prompt| &prompt.kind),
+            Some(&PromptKind::DatabasePassphrase)
+        );
+
+        // Quitting before typing the passphrase keeps the file
+        let mut locked = Model::new(model.file.clone());
+        locked.load_from_cache()?;
+        locked.save_to_cache()?;
+        assert!(is_encrypted(Path::new(&model.file))?);
+
+        assert!(!model.unlock_database("battery staple"));
+        assert!(model.unlock_database("correct horse"));
+        assert!(model.load_from_cache()?);
+        assert_eq!(model.entries[0].entries[0].command, "command1");
+        assert_eq!(model.stats_of("command1").count, 1);
+
+        // Saved in plain text again without passphrase
+        model.encrypt_database("");
+        model.save_to_cache()?;
+        assert!(!is_encrypted(Path::new(&model.file))?);
+
+        // Cleaning
+        std::fs::remove_dir_all("./path_unlock")?;
+        Ok(())
+    }
+
+    #[test]
+    fn read_only_groups_are_not_saved() -> std::io::Result<()> {
+        let mut model = Model::new(String::from("./path_read_only/test.cache"));
+        model.entries = vec![
+            make_test_entry_group(),
+            EntryGroup::new_read_only(String::from("virtual"), vec![]),
+        ];
+
+        model.save_to_cache()?;
+
+        model.entries.clear();
+        model.load_from_cache()?;
+
+        assert_eq!(model.entries.len(), 1);
+        assert_eq!(model.entries[0].description, "description");
+
+        // Cleaning
+        std::fs::remove_dir_all("./path_read_only")?;
+        Ok(())
+    }
 }
@@ -0,0 +1,187 @@
+use crate::model::history::{Edit, GroupInfo};
+use crate::model::{CurrentScreen, Model};
+
+impl Model {
+    /// Pin or unpin the selected entry group (main screen) or entry (secondary screen)
+    pub fn toggle_pin(&mut self) {
+        match self.current_screen {
+            CurrentScreen::Main => {
+                let Some(entry_group) = self.entries.get(self.idx_entrygroup) else {
+                    return;
+                };
+                if entry_group.read_only {
+                    return;
+                }
+                let before = GroupInfo::of(entry_group);
+                let after = GroupInfo {
+                    pinned: !before.pinned,
+                    ..before.clone()
+                };
+                self.edit(
+                    format!("{} '{}'", pin_verb(after.pinned), before.description),
+                    vec![Edit::ModifyGroup {
+                        idx: self.idx_entrygroup,
+                        before,
+                        after,
+                    }],
+                );
+            }
+            CurrentScreen::Secondary => {
+                let entry = &self.entries[self.idx_entrygroup].entries[self.idx_entry];
+                let pinned = !entry.pinned;
+                let command = entry.command.clone();
+
+                // Entries that are not stored (e.g. project tasks) cannot be pinned
+                if !self.is_stored(&command) {
+                    self.status = Some(String::from("Only the stored commands can be pinned"));
+                    return;
+                }
+                self.edit_every_copy(format!("{} '{}'", pin_verb(pinned), command), |entry| {
+                    entry.pinned = pinned;
+                });
+            }
+        }
+    }
+
+    /// Position (group, entry) of the pinned entries, in the order they are shown
+    pub fn pinned_entries(&self) -> Vec<(usize, usize)> {
+        self.group_order()
+            .into_iter()
+            .filter(|&idx_entrygroup| !self.entries[idx_entrygroup].read_only)
+            .flat_map(|idx_entrygroup| {
+                self.entries[idx_entrygroup]
+                    .entries
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, entry)| entry.pinned)
+                    .map(move |(idx_entry, _)| (idx_entrygroup, idx_entry))
+            })
+            .collect()
+    }
+
+    /// Select the nth pinned entry, starting at 1. Returns whether it exists
+    pub fn select_pinned(&mut self, n: usize) -> bool {
+        match n
+            .checked_sub(1)
+            .and_then(|i| self.pinned_entries().get(i).copied())
+        {
+            Some((idx_entrygroup, idx_entry)) => {
+                self.idx_entrygroup = idx_entrygroup;
+                self.idx_entry = idx_entry;
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+fn pin_verb(pinned: bool) -> &'static str {
+    match pinned {
+        true => "pin",
+        false => "unpin",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entry::{Entry, EntryGroup};
+    use crate::model::tests::make_test_entry_group;
+
+    #[test]
+    fn toggle_pin_group_and_entry() {
+        let mut model = Model {
+            entries: vec![make_test_entry_group(), make_test_entry_group()],
+            idx_entrygroup: 1,
+            ..Default::default()
+        };
+
+        model.toggle_pin();
+        assert!(model.entries[1].pinned);
+        assert_eq!(model.group_order(), vec![1, 0]);
+
+        model.current_screen = CurrentScreen::Secondary;
+        model.idx_entry = 1;
+        model.toggle_pin();
+        assert!(model.entries[1].entries[1].pinned);
+        assert!(!model.entries[1].entries[0].pinned);
+
+        model.toggle_pin();
+        assert!(!model.entries[1].entries[1].pinned);
+    }
+
+    #[test]
+    fn pin_from_read_only_group() {
+        let mut recent = make_test_entry_group();
+        recent.read_only = true;
+        let task = Entry {
+            command: String::from("make build"),
+            ..Default::default()
+        };
+        let project = EntryGroup::new_read_only(String::from("make"), vec![task]);
+        let mut model = Model {
+            entries: vec![recent, make_test_entry_group(), project],
+            current_screen: CurrentScreen::Secondary,
+            ..Default::default()
+        };
+
+        model.toggle_pin();
+        assert!(model.entries[0].entries[0].pinned);
+        assert!(model.entries[1].entries[0].pinned);
+        assert_eq!(model.pinned_entries(), vec![(1, 0)]);
+
+        // Read-only groups cannot be pinned
+        model.current_screen = CurrentScreen::Main;
+        model.idx_entrygroup = 2;
+        model.toggle_pin();
+        assert!(!model.entries[2].pinned);
+
+        // Nor the entries that are not stored
+        model.current_screen = CurrentScreen::Secondary;
+        model.toggle_pin();
+        assert!(!model.entries[2].entries[0].pinned);
+        assert_eq!(
+            model.status.as_deref(),
+            Some("Only the stored commands can be pinned")
+        );
+    }
+
+    #[test]
+    fn undo_pin() {
+        let mut recent = make_test_entry_group();
+        recent.read_only = true;
+        let mut model = Model {
+            entries: vec![recent, make_test_entry_group()],
+            idx_entrygroup: 1,
+            idx_entry: 1,
+            current_screen: CurrentScreen::Secondary,
+            ..Default::default()
+        };
+
+        model.toggle_pin();
+        assert!(model.entries[0].entries[1].pinned);
+        model.undo();
+        assert!(!model.entries[0].entries[1].pinned);
+        assert!(!model.entries[1].entries[1].pinned);
+        assert_eq!(model.status.as_deref(), Some("Undone: pin 'command2'"));
+        assert_eq!((model.idx_entrygroup, model.idx_entry), (1, 1));
+    }
+
+    #[test]
+    fn select_nth_pinned_entry() {
+        let mut model = Model {
+            entries: vec![make_test_entry_group(), make_test_entry_group()],
+            ..Default::default()
+        };
+        model.entries[0].entries[1].pinned = true;
+        model.entries[1].entries[0].pinned = true;
+
+        assert!(!model.select_pinned(0));
+        assert!(model.select_pinned(2));
+        assert_eq!((model.idx_entrygroup, model.idx_entry), (1, 0));
+        assert!(model.select_pinned(1));
+        assert_eq!((model.idx_entrygroup, model.idx_entry), (0, 1));
+        assert!(!model.select_pinned(3));
+        assert_eq!((model.idx_entrygroup, model.idx_entry), (0, 1));
+    }
+}
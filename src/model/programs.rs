@@ -0,0 +1,217 @@
+//! Programs run by the commands, looked up on the search path to flag the ones that are not
+//! installed on this machine
+
+use crate::model::{CurrentScreen, Model};
+use std::collections::HashSet;
+use std::ffi::OsStr;
+use std::path::{Path, PathBuf};
+
+/// Words running the program that follows them
+const WRAPPERS: [&str; 7] = ["sudo", "env", "time", "nohup", "exec", "command", "xargs"];
+
+/// Options of the wrappers followed by a value, e.g. the user of `sudo -u postgres psql`
+const WRAPPER_OPTIONS: [(&str, &[&str]); 3] = [
+    (
+        "sudo",
+        &["-u", "--user", "-g", "--group", "-C", "--close-from"],
+    ),
+    ("env", &["-u", "--unset", "-C", "--chdir"]),
+    ("xargs", &["-I", "-n", "--max-args", "-P", "--max-procs"]),
+];
+
+/// Shell builtins and keywords, which are not looked up
+const BUILTINS: [&str; 24] = [
+    "cd", "echo", "export", "source", ".", "alias", "unset", "set", "exit", "read", "eval", "test",
+    "[", "printf", "pwd", "type", "ulimit", "umask", "wait", "history", "for", "if", "while",
+    "case",
+];
+
+/// Program run by a command, e.g. `cargo` for `RUST_LOG=debug cargo run`. None when it
+/// cannot be looked up: builtins, paths, placeholders and variables
+pub fn program_of(command: &str) -> Option<&str> {
+    let mut words = command.split_whitespace();
+    let mut wrapper = None;
+    let program = loop {
+        let word = words.next()?;
+        if WRAPPERS.contains(&word) {
+            wrapper = Some(word);
+        } else if word.starts_with('-') {
+            if takes_value(wrapper, word) {
+                words.next();
+            }
+        } else if !is_assignment(word) {
+            break word;
+        }
+    };
+    let special = ['/', '<', '$', '"', '\'', '(', '`', '{', '='];
+    match program.contains(special) || BUILTINS.contains(&program) {
+        true => None,
+        false => Some(program),
+    }
+}
+
+/// Whether the option of the wrapper is followed by a value
+fn takes_value(wrapper: Option<&str>, option: &str) -> bool {
+    WRAPPER_OPTIONS
+        .iter()
+        .any(|(name, options)| wrapper == Some(*name) && options.contains(&option))
+}
+
+/// Whether the word sets a variable of the environment, e.g. `RUST_LOG=debug`
+fn is_assignment(word: &str) -> bool {
+    word.split_once('=').is_some_and(|(name, _)| {
+        !name.is_empty() && name.chars().all(|c| c.is_alphanumeric() || c == '_')
+    })
+}
+
+/// Executable file of a program in the directories of `search_path`, like $PATH
+pub fn find_program(program: &str, search_path: &OsStr) -> Option<PathBuf> {
+    std::env::split_paths(search_path)
+        .map(|dir| dir.join(program))
+        .find(|path| is_executable(path))
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    path.metadata()
+        .is_ok_and(|metadata| metadata.is_file() && metadata.permissions().mode() & 0o111 != 0)
+}
+
+#[cfg(not(unix))]
+fn is_executable(path: &Path) -> bool {
+    path.is_file() || path.with_extension("exe").is_file()
+}
+
+impl Model {
+    /// Look up the programs of every command on the search path, to flag the missing ones
+    pub fn check_programs(&mut self) {
+        self.missing_programs.clear();
+        let Some(search_path) = &self.search_path else {
+            return;
+        };
+        let programs: HashSet<&str> = self
+            .entries
+            .iter()
+            .flat_map(|entry_group| &entry_group.entries)
+            .filter_map(|entry| program_of(&entry.command))
+            .collect();
+        self.missing_programs = programs
+            .into_iter()
+            .filter(|program| find_program(program, search_path).is_none())
+            .map(String::from)
+            .collect();
+        tracing::debug!(missing = ?self.missing_programs, "looked up the programs");
+    }
+
+    /// Whether the program of the command is not installed
+    pub fn is_missing(&self, command: &str) -> bool {
+        program_of(command).is_some_and(|program| self.missing_programs.contains(program))
+    }
+
+    /// Show or hide the entries whose program is not installed. The selection moves to a
+    /// shown entry, or back to the main screen if the group has none
+    pub fn toggle_hide_missing(&mut self) {
+        self.hide_missing = !self.hide_missing;
+        self.status = Some(String::from(match self.hide_missing {
+            true => "Entries of missing programs hidden",
+            false => "Entries of missing programs shown",
+        }));
+
+        if self.current_screen == CurrentScreen::Secondary {
+            let order = self.entry_order();
+            if order.is_empty() {
+                self.current_screen = CurrentScreen::Main;
+                self.zoomed = false;
+            } else if !order.contains(&self.idx_entry) {
+                self.select_first_entry();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::history::Edit;
+    use crate::model::tests::make_test_entry_group;
+
+    #[test]
+    fn find_the_program_of_commands() {
+        assert_eq!(program_of("cargo run --release"), Some("cargo"));
+        assert_eq!(program_of("RUST_LOG=debug cargo run"), Some("cargo"));
+        assert_eq!(program_of("sudo -E apt install <package>"), Some("apt"));
+        assert_eq!(program_of("g++ -o main main.cpp"), Some("g++"));
+        assert_eq!(program_of("sudo -u postgres psql"), Some("psql"));
+        assert_eq!(program_of("env -C dir make"), Some("make"));
+        assert_eq!(program_of("xargs -I{} cp {} backup/"), Some("cp"));
+        assert_eq!(program_of("xargs -I {} -P 4 cp {} backup/"), Some("cp"));
+        assert_eq!(program_of("cd build && make"), None);
+        assert_eq!(program_of("./configure --prefix=/usr"), None);
+        assert_eq!(program_of("<editor> file.txt"), None);
+        assert_eq!(program_of("$EDITOR file.txt"), None);
+        assert_eq!(program_of(""), None);
+    }
+
+    #[test]
+    fn flag_and_hide_missing_programs() -> std::io::Result<()> {
+        let dir = std::env::temp_dir().join("memors-programs");
+        std::fs::create_dir_all(&dir)?;
+        std::fs::write(dir.join("command1"), "")?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::set_permissions(dir.join("command1"), std::fs::Permissions::from_mode(0o755))?;
+        }
+
+        let mut model = Model {
+            entries: vec![make_test_entry_group()],
+            current_screen: CurrentScreen::Secondary,
+            idx_entry: 1,
+            search_path: Some(dir.clone().into_os_string()),
+            ..Default::default()
+        };
+        model.check_programs();
+        assert!(!model.is_missing("command1 --flag"));
+        assert!(model.is_missing("command2"));
+        assert!(find_program("command1", dir.as_os_str()).is_some());
+
+        // The selected entry is hidden, so the first shown one is selected
+        model.toggle_hide_missing();
+        assert_eq!(model.entry_order(), [0]);
+        assert_eq!(model.idx_entry, 0);
+
+        // Nothing is shown for a group whose programs are all missing
+        model.entries[0].entries[0].command = String::from("command2 --flag");
+        model.toggle_hide_missing();
+        model.check_programs();
+        model.toggle_hide_missing();
+        assert!(model.entry_order().is_empty());
+        assert_eq!(model.current_screen, CurrentScreen::Main);
+
+        // Edits flag the programs again
+        let mut entry = model.entries[0].entries[0].clone();
+        entry.command = String::from("command1 --flag");
+        model.edit(
+            String::from("edit"),
+            vec![Edit::ModifyEntry {
+                group: 0,
+                idx: 0,
+                before: model.entries[0].entries[0].clone(),
+                after: entry,
+            }],
+        );
+        assert_eq!(model.entry_order(), [0]);
+        model.undo();
+        assert!(model.is_missing(&model.entries[0].entries[0].command));
+
+        // Nothing is looked up without search path
+        model.search_path = None;
+        model.check_programs();
+        assert!(!model.is_missing("command2"));
+
+        // Cleaning
+        std::fs::remove_dir_all(dir)?;
+        Ok(())
+    }
+}
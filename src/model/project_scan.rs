@@ -0,0 +1,382 @@
+//! Tasks of the project in the current directory, shown as read-only groups: the targets of
+//! its Makefile, the recipes of its justfile, the scripts of its package.json and the cargo
+//! aliases of its .cargo/config.toml
+
+use crate::entry::{Entry, EntryGroup};
+use crate::model::Model;
+use std::fs;
+use std::path::Path;
+
+/// Files that declare runnable tasks and are looked up in the project directory
+const MAKEFILE_NAMES: [&str; 3] = ["GNUmakefile", "makefile", "Makefile"];
+const JUSTFILE_NAMES: [&str; 3] = ["justfile", "Justfile", ".justfile"];
+const PACKAGE_JSON_NAME: &str = "package.json";
+const CARGO_CONFIG_NAMES: [&str; 2] = [".cargo/config.toml", ".cargo/config"];
+
+impl Model {
+    /// Discover the tasks declared in the given project directory and append them
+    /// as read-only entry groups, one per source file
+    pub fn scan_project(&mut self, dir: &Path) {
+        let groups = scan_dir(dir);
+        tracing::debug!(dir = %dir.display(), groups = groups.len(), "scanned the project tasks");
+        self.entries.extend(groups);
+    }
+}
+
+/// Look for every supported task file in `dir` and build one group per file found
+pub(crate) fn scan_dir(dir: &Path) -> Vec<EntryGroup> {
+    let mut groups = vec![];
+
+    if let Some((path, content)) = read_first(dir, &MAKEFILE_NAMES) {
+        push_group(&mut groups, "make", &path, parse_makefile(&content));
+    }
+
+    if let Some((path, content)) = read_first(dir, &JUSTFILE_NAMES) {
+        push_group(&mut groups, "just", &path, parse_justfile(&content));
+    }
+
+    if let Some((path, content)) = read_first(dir, &[PACKAGE_JSON_NAME]) {
+        push_group(&mut groups, "npm", &path, parse_package_json(&content));
+    }
+
+    if let Some((path, content)) = read_first(dir, &CARGO_CONFIG_NAMES) {
+        push_group(&mut groups, "cargo", &path, parse_cargo_aliases(&content));
+    }
+
+    groups
+}
+
+/// Return the path and the content of the first readable file among `names`
+fn read_first(dir: &Path, names: &[&str]) -> Option<(String, String)> {
+    names.iter().find_map(|name| {
+        let path = dir.join(name);
+        fs::read_to_string(&path)
+            .ok()
+            .map(|content| (name.to_string(), content))
+    })
+}
+
+/// Add a group for `file` if it declares at least one task
+fn push_group(groups: &mut Vec<EntryGroup>, tool: &str, file: &str, mut tasks: Vec<Entry>) {
+    if tasks.is_empty() {
+        return;
+    }
+
+    for task in tasks.iter_mut() {
+        task.long_info = format!("Declared in {}", file);
+    }
+
+    groups.push(EntryGroup::new_read_only(
+        format!("{} ({})", tool, file),
+        tasks,
+    ));
+}
+
+/// Build a task entry
+fn make_task(command: String, short_info: String) -> Entry {
+    Entry {
+        command,
+        short_info,
+        long_info: String::new(),
+        ..Default::default()
+    }
+}
+
+/// Extract the explicit targets of a Makefile. A `## text` suffix or the comment
+/// right above the rule is used as description
+pub(crate) fn parse_makefile(content: &str) -> Vec<Entry> {
+    let mut tasks: Vec<Entry> = vec![];
+    let mut last_comment = String::new();
+
+    for line in content.lines() {
+        // Recipe lines and blank lines
+        if line.starts_with('\t') || line.trim().is_empty() {
+            last_comment.clear();
+            continue;
+        }
+
+        if let Some(comment) = line.strip_prefix('#') {
+            last_comment = comment.trim_start_matches('#').trim().to_string();
+            continue;
+        }
+
+        let comment = std::mem::take(&mut last_comment);
+
+        // Rules look like `targets: prerequisites`, but `:=` and `::=` are assignments
+        let Some(colon) = line.find(':') else {
+            continue;
+        };
+        let (targets, rest) = line.split_at(colon);
+        if rest.starts_with(":=") || rest.starts_with("::=") || targets.contains('=') {
+            continue;
+        }
+
+        let description = match rest.split_once("##") {
+            Some((_, desc)) => desc.trim().to_string(),
+            None => comment,
+        };
+
+        for target in targets.split_whitespace() {
+            // Special targets (.PHONY), pattern rules and variable references are not tasks
+            if target.starts_with('.') || target.contains('%') || target.contains('$') {
+                continue;
+            }
+            if tasks
+                .iter()
+                .any(|t| t.command == format!("make {}", target))
+            {
+                continue;
+            }
+            tasks.push(make_task(format!("make {}", target), description.clone()));
+        }
+    }
+
+    tasks
+}
+
+/// Extract the recipes of a justfile. The comment right above the recipe is used as description
+pub(crate) fn parse_justfile(content: &str) -> Vec<Entry> {
+    let mut tasks: Vec<Entry> = vec![];
+    let mut last_comment = String::new();
+
+    for line in content.lines() {
+        // Recipe bodies and blank lines
+        if line.starts_with(' ') || line.starts_with('\t') || line.trim().is_empty() {
+            last_comment.clear();
+            continue;
+        }
+
+        if let Some(comment) = line.strip_prefix('#') {
+            // Shebangs are not documentation
+            if !comment.starts_with('!') {
+                last_comment = comment.trim().to_string();
+            }
+            continue;
+        }
+
+        // Attributes such as `[private]` keep the previous comment
+        if line.starts_with('[') {
+            if line.trim() == "[private]" {
+                last_comment = String::from("[private]");
+            }
+            continue;
+        }
+
+        let comment = std::mem::take(&mut last_comment);
+
+        let Some(colon) = line.find(':') else {
+            continue;
+        };
+        let (header, rest) = line.split_at(colon);
+        if rest.starts_with(":=") {
+            continue;
+        }
+
+        let mut words = header.split_whitespace();
+        let Some(name) = words.next() else {
+            continue;
+        };
+        let name = name.trim_start_matches('@');
+
+        // Settings, aliases and variable declarations
+        if matches!(name, "set" | "alias" | "export" | "import" | "mod")
+            || !name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+            || name.starts_with('_')
+            || comment == "[private]"
+        {
+            continue;
+        }
+
+        // Required parameters are shown as placeholders
+        let params: Vec<String> = words
+            .filter(|p| !p.contains('='))
+            .map(|p| format!("<{}>", p.trim_start_matches(['+', '*', '$'])))
+            .collect();
+
+        let command = match params.is_empty() {
+            true => format!("just {}", name),
+            false => format!("just {} {}", name, params.join(" ")),
+        };
+
+        tasks.push(make_task(command, comment));
+    }
+
+    tasks
+}
+
+/// Extract the `scripts` of a package.json. The script itself is shown as description
+pub(crate) fn parse_package_json(content: &str) -> Vec<Entry> {
+    let Ok(json) = serde_json::from_str::<serde_json::Value>(content) else {
+        return vec![];
+    };
+
+    let Some(scripts) = json.get("scripts").and_then(|s| s.as_object()) else {
+        return vec![];
+    };
+
+    scripts
+        .iter()
+        .filter_map(|(name, script)| {
+            script
+                .as_str()
+                .map(|script| make_task(format!("npm run {}", name), script.to_string()))
+        })
+        .collect()
+}
+
+/// Extract the `[alias]` table of a cargo configuration file
+pub(crate) fn parse_cargo_aliases(content: &str) -> Vec<Entry> {
+    let Ok(config) = content.parse::<toml::Table>() else {
+        return vec![];
+    };
+
+    let Some(aliases) = config.get("alias").and_then(|a| a.as_table()) else {
+        return vec![];
+    };
+
+    aliases
+        .iter()
+        .filter_map(|(name, expansion)| {
+            let expansion = match expansion {
+                toml::Value::String(s) => s.clone(),
+                toml::Value::Array(args) => args
+                    .iter()
+                    .filter_map(|arg| arg.as_str())
+                    .collect::<Vec<&str>>()
+                    .join(" "),
+                _ => return None,
+            };
+            Some(make_task(
+                format!("cargo {}", name),
+                format!("cargo {}", expansion),
+            ))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn commands(tasks: &[Entry]) -> Vec<&str> {
+        tasks.iter().map(|t| t.command.as_str()).collect()
+    }
+
+    #[test]
+    fn parse_makefile_targets() {
+        let content = "\
+CC := gcc
+FLAGS = -O2
+.PHONY: build test
+
+# Compile everything
+build: main.o
+\t$(CC) $(FLAGS) main.o
+
+test: build ## Run the test suite
+\t./run_tests
+
+%.o: %.c
+\t$(CC) -c $<
+
+clean install:
+\trm -f *.o
+";
+        let tasks = parse_makefile(content);
+
+        assert_eq!(
+            commands(&tasks),
+            vec!["make build", "make test", "make clean", "make install"]
+        );
+        assert_eq!(tasks[0].short_info, "Compile everything");
+        assert_eq!(tasks[1].short_info, "Run the test suite");
+        assert_eq!(tasks[2].short_info, "");
+    }
+
+    #[test]
+    fn parse_justfile_recipes() {
+        let content = "\
+set shell := [\"bash\", \"-c\"]
+alias b := build
+version := \"1.0\"
+
+# Build the project
+build:
+    cargo build
+
+# Deploy to an environment
+deploy env target=\"all\":
+    ./deploy.sh {{env}} {{target}}
+
+[private]
+helper:
+    echo hidden
+
+_hidden:
+    echo hidden
+";
+        let tasks = parse_justfile(content);
+
+        assert_eq!(commands(&tasks), vec!["just build", "just deploy <env>"]);
+        assert_eq!(tasks[0].short_info, "Build the project");
+        assert_eq!(tasks[1].short_info, "Deploy to an environment");
+    }
+
+    #[test]
+    fn parse_package_json_scripts() {
+        let content = r#"{
+            "name": "demo",
+            "scripts": { "build": "tsc", "test": "jest --coverage" }
+        }"#;
+        let tasks = parse_package_json(content);
+
+        assert_eq!(commands(&tasks), vec!["npm run build", "npm run test"]);
+        assert_eq!(tasks[1].short_info, "jest --coverage");
+
+        assert!(parse_package_json("{}").is_empty());
+        assert!(parse_package_json("not json").is_empty());
+    }
+
+    #[test]
+    fn parse_cargo_config_aliases() {
+        let content = r#"
+[build]
+jobs = 4
+
+[alias]
+b = "build"
+rr = ["run", "--release"]
+"#;
+        let tasks = parse_cargo_aliases(content);
+
+        assert_eq!(commands(&tasks), vec!["cargo b", "cargo rr"]);
+        assert_eq!(tasks[0].short_info, "cargo build");
+        assert_eq!(tasks[1].short_info, "cargo run --release");
+    }
+
+    #[test]
+    fn scan_project_directory() -> std::io::Result<()> {
+        let dir = Path::new("./path_project_scan");
+        fs::create_dir_all(dir.join(".cargo"))?;
+        fs::write(dir.join("Makefile"), "all:\n\techo all\n")?;
+        fs::write(dir.join(".cargo/config.toml"), "[alias]\nb = \"build\"\n")?;
+
+        let mut model = Model::default();
+        model.scan_project(dir);
+
+        assert_eq!(model.entries.len(), 2);
+        assert!(model.entries.iter().all(|eg| eg.read_only));
+        assert_eq!(model.entries[0].description, "make (Makefile)");
+        assert_eq!(
+            model.entries[0].entries[0].long_info,
+            "Declared in Makefile"
+        );
+        assert_eq!(model.entries[1].entries[0].command, "cargo b");
+
+        // Cleaning
+        fs::remove_dir_all(dir)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,170 @@
+//! Single-line text typed in a popup over the screens, e.g. a passphrase
+
+use crate::model::secrets::SecretAction;
+use crate::model::{Model, RunningState};
+
+/// What the typed text is for
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PromptKind {
+    /// Passphrase of the secrets, asked before the action needing them
+    Passphrase(SecretAction),
+    /// Name of the placeholder of the selected command to keep secret
+    SecretName,
+    /// Value of the given secret placeholder
+    SecretValue(String),
+    /// Passphrase of the encrypted database, asked when loading it
+    DatabasePassphrase,
+    /// Passphrase to encrypt the database with from now on
+    NewDatabasePassphrase,
+    /// Review of the long description generated for the selected command, which is not typed
+    LongInfo(String),
+}
+
+/// Text being typed, and what it is for
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Prompt {
+    pub kind: PromptKind,
+    pub input: String,
+}
+
+impl Prompt {
+    pub fn new(kind: PromptKind) -> Prompt {
+        Prompt {
+            kind,
+            input: String::new(),
+        }
+    }
+
+    /// Question shown above the typed text
+    pub fn title(&self) -> String {
+        match &self.kind {
+            PromptKind::Passphrase(_) => String::from("Passphrase of the secrets"),
+            PromptKind::SecretName => String::from("Placeholder to keep secret"),
+            PromptKind::SecretValue(name) => format!("Value of <{}>, empty to forget it", name),
+            PromptKind::DatabasePassphrase => String::from("Passphrase of the database"),
+            PromptKind::NewDatabasePassphrase => {
+                String::from("New passphrase of the database, empty for none")
+            }
+            PromptKind::LongInfo(_) => {
+                String::from("Generated description: Enter to keep it, Esc to discard it")
+            }
+        }
+    }
+
+    /// Whether text is typed, or the prompt only asks to confirm
+    pub fn typed(&self) -> bool {
+        !matches!(self.kind, PromptKind::LongInfo(_))
+    }
+
+    /// Whether the typed text is hidden
+    pub fn masked(&self) -> bool {
+        !matches!(self.kind, PromptKind::SecretName | PromptKind::LongInfo(_))
+    }
+}
+
+impl Model {
+    pub fn prompt_input(&mut self, c: char) {
+        if let Some(prompt) = self.prompt.as_mut().filter(|prompt| prompt.typed()) {
+            prompt.input.push(c);
+        }
+    }
+
+    pub fn prompt_backspace(&mut self) {
+        if let Some(prompt) = self.prompt.as_mut().filter(|prompt| prompt.typed()) {
+            prompt.input.pop();
+        }
+    }
+
+    /// Close the prompt. Without the passphrase of the database, there is nothing to show,
+    /// so the application quits without saving
+    pub fn cancel_prompt(&mut self) {
+        if let Some(PromptKind::DatabasePassphrase) = self.prompt.take().map(|prompt| prompt.kind) {
+            self.running_state = RunningState::Done;
+        }
+    }
+
+    /// Close the prompt and use the typed text. Returns the action that was waiting for
+    /// the passphrase, if it was the right one
+    pub fn submit_prompt(&mut self) -> Option<SecretAction> {
+        let prompt = self.prompt.take()?;
+        match prompt.kind {
+            PromptKind::Passphrase(action) => match self.unlock_secrets(&prompt.input) {
+                true => return Some(action),
+                false => self.status = Some(String::from("Wrong passphrase")),
+            },
+            PromptKind::SecretName => {
+                let command = &self.entries[self.idx_entrygroup].entries[self.idx_entry].command;
+                let name = prompt.input.trim().to_string();
+                match super::secrets::placeholders(command).contains(&name.as_str()) {
+                    true => self.prompt = Some(Prompt::new(PromptKind::SecretValue(name))),
+                    false => {
+                        self.status = Some(format!("The command has no placeholder <{}>", name))
+                    }
+                }
+            }
+            PromptKind::SecretValue(name) => self.store_secret(&name, &prompt.input),
+            // Asked again until the right one is typed
+            PromptKind::DatabasePassphrase => match self.unlock_database(&prompt.input) {
+                true => return Some(SecretAction::OpenDatabase),
+                false => {
+                    self.status = Some(String::from("Wrong passphrase"));
+                    self.prompt = Some(Prompt::new(PromptKind::DatabasePassphrase));
+                }
+            },
+            PromptKind::NewDatabasePassphrase => self.encrypt_database(&prompt.input),
+            PromptKind::LongInfo(long_info) => self.set_long_info(long_info),
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::tests::make_test_entry_group;
+    use crate::model::CurrentScreen;
+
+    #[test]
+    fn type_in_prompts() {
+        let mut model = Model {
+            entries: vec![make_test_entry_group()],
+            current_screen: CurrentScreen::Secondary,
+            ..Default::default()
+        };
+        model.entries[0].entries[0].command = String::from("ssh <host>");
+        model.unlock_secrets("passphrase");
+
+        model.start_storing_secret();
+        assert_eq!(model.prompt.as_ref().unwrap().input, "host");
+        model.prompt_backspace();
+        model.prompt_input('x');
+        assert_eq!(model.submit_prompt(), None);
+        assert_eq!(
+            model.status.as_deref(),
+            Some("The command has no placeholder <hosx>")
+        );
+
+        model.start_storing_secret();
+        model.submit_prompt();
+        assert!(model.prompt.as_ref().unwrap().masked());
+        "example.org".chars().for_each(|c| model.prompt_input(c));
+        model.submit_prompt();
+        assert!(model.prompt.is_none());
+        assert!(model.entries[0].entries[0].secrets.contains_key("host"));
+
+        // Wrong passphrases do not do the waiting action
+        model.secret_key = None;
+        assert!(!model.unlock_for(SecretAction::Reveal));
+        model.prompt_input('x');
+        assert_eq!(model.submit_prompt(), None);
+        assert_eq!(model.status.as_deref(), Some("Wrong passphrase"));
+
+        assert!(!model.unlock_for(SecretAction::Reveal));
+        "passphrase".chars().for_each(|c| model.prompt_input(c));
+        assert_eq!(model.submit_prompt(), Some(SecretAction::Reveal));
+
+        model.start_storing_secret();
+        model.cancel_prompt();
+        assert!(model.prompt.is_none());
+    }
+}
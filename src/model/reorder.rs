@@ -0,0 +1,327 @@
+use crate::entry::Entry;
+use crate::model::history::Edit;
+use crate::model::usage::SortMode;
+use crate::model::{position, CurrentScreen, Model};
+
+impl Model {
+    /// Move the selected entry group (main screen) or entry (secondary screen) one place
+    /// up or down in the shown list. The selection follows the moved item
+    pub fn move_selected(&mut self, down: bool) {
+        let Some(entry_group) = self.entries.get(self.idx_entrygroup) else {
+            return;
+        };
+        let read_only = entry_group.read_only;
+        let sort = match self.current_screen {
+            CurrentScreen::Main => self.group_sort,
+            CurrentScreen::Secondary => self.entry_sort,
+        };
+        if sort != SortMode::Manual {
+            self.status = Some(String::from("Switch to the manual order to move items"));
+            return;
+        }
+        if read_only {
+            self.status = Some(String::from("Virtual groups cannot be changed"));
+            return;
+        }
+
+        match self.current_screen {
+            CurrentScreen::Main => {
+                // Groups only swap with their siblings of the same kind, pinned groups
+                // being always shown first. Child groups move along with their parent
+                let from = self.idx_entrygroup;
+                let pinned = self.entries[from].pinned;
+                let order: Vec<usize> = self
+                    .group_order()
+                    .into_iter()
+                    .filter(|&i| {
+                        let group = &self.entries[i];
+                        !group.read_only
+                            && group.pinned == pinned
+                            && group.depth == self.entries[from].depth
+                            && self.parent_of(i) == self.parent_of(from)
+                    })
+                    .collect();
+                if let Some(sibling) = neighbour(&order, from, down) {
+                    let len = self.subtree_len(from);
+                    let to = match sibling > from {
+                        true => sibling + self.subtree_len(sibling) - len,
+                        false => sibling,
+                    };
+                    let description = self.entries[from].description.clone();
+                    self.edit(
+                        format!("move '{}'", description),
+                        vec![Edit::MoveGroup { from, len, to }],
+                    );
+                }
+            }
+            CurrentScreen::Secondary => {
+                let order = self.entry_order();
+                if let Some(to) = neighbour(&order, self.idx_entry, down) {
+                    let group = self.idx_entrygroup;
+                    let command = self.entries[group].entries[self.idx_entry].command.clone();
+                    self.edit(
+                        format!("move '{}'", command),
+                        vec![Edit::MoveEntry {
+                            from: (group, self.idx_entry),
+                            to: (group, to),
+                        }],
+                    );
+                }
+            }
+        }
+    }
+
+    /// Keep the selected entry to be moved in another group when pasted. It is left in
+    /// place until then, not to be lost if never pasted
+    pub fn cut_entry(&mut self) {
+        let group = self.idx_entrygroup;
+        if self.entries[group].read_only {
+            self.status = Some(String::from("Virtual groups cannot be changed"));
+            return;
+        }
+
+        let entry = self.entries[group].entries[self.idx_entry].clone();
+        self.status = Some(format!("Cut '{}', paste it with (v)", entry.command));
+        self.cut = Some(((group, self.idx_entry), entry));
+    }
+
+    /// Move the last cut entry after the selected entry (secondary screen) or at the end of
+    /// the selected group (main screen)
+    pub fn paste_entry(&mut self) {
+        let Some((location, entry)) = self.cut.clone() else {
+            self.status = Some(String::from("Nothing to paste, cut an entry with (x)"));
+            return;
+        };
+        let group = self.idx_entrygroup;
+        if self.entries[group].read_only {
+            self.status = Some(String::from("Virtual groups cannot be changed"));
+            return;
+        }
+        // The entry may have moved since it was cut
+        let Some(from) = self.find_entry(location, &entry) else {
+            self.status = Some(format!("'{}' is no longer in the database", entry.command));
+            self.cut = None;
+            return;
+        };
+
+        let mut idx = match self.current_screen {
+            CurrentScreen::Main => self.entries[group].entries.len(),
+            CurrentScreen::Secondary => self.idx_entry + 1,
+        };
+        if from.0 == group && from.1 < idx {
+            idx -= 1;
+        }
+        self.edit(
+            format!("paste '{}'", entry.command),
+            vec![Edit::MoveEntry {
+                from,
+                to: (group, idx),
+            }],
+        );
+        self.cut = None;
+    }
+
+    /// Location of the entry in the stored groups, preferably the given one
+    fn find_entry(&self, location: (usize, usize), entry: &Entry) -> Option<(usize, usize)> {
+        let (group, idx) = location;
+        let stored = |group: usize| !self.entries[group].read_only;
+        if group < self.entries.len()
+            && stored(group)
+            && self.entries[group].entries.get(idx) == Some(entry)
+        {
+            return Some(location);
+        }
+        self.entries
+            .iter()
+            .enumerate()
+            .filter(|&(group, _)| stored(group))
+            .find_map(|(group, entry_group)| {
+                let idx = entry_group.entries.iter().position(|e| e == entry)?;
+                Some((group, idx))
+            })
+    }
+
+    /// Insert a copy of the selected entry right after it, and select the copy
+    pub fn duplicate_entry(&mut self) {
+        let group = self.idx_entrygroup;
+        if self.entries[group].read_only {
+            self.status = Some(String::from("Virtual groups cannot be changed"));
+            return;
+        }
+
+        // The copy is not pinned, so that the pinned bar has no duplicates
+        let entry = Entry {
+            pinned: false,
+            ..self.entries[group].entries[self.idx_entry].clone()
+        };
+        self.edit(
+            format!("duplicate '{}'", entry.command),
+            vec![Edit::InsertEntry {
+                group,
+                idx: self.idx_entry + 1,
+                entry,
+            }],
+        );
+    }
+}
+
+/// Index shown right after (or before) `idx` in `order`, if any
+fn neighbour(order: &[usize], idx: usize, down: bool) -> Option<usize> {
+    let pos = position(order, idx);
+    match down {
+        true => order.get(pos + 1).copied(),
+        false => pos.checked_sub(1).map(|pos| order[pos]),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entry::EntryGroup;
+    use crate::model::tests::make_test_entry_group;
+
+    fn descriptions(model: &Model) -> Vec<&str> {
+        model
+            .entries
+            .iter()
+            .map(|eg| eg.description.as_str())
+            .collect()
+    }
+
+    fn commands(model: &Model, group: usize) -> Vec<&str> {
+        model.entries[group]
+            .entries
+            .iter()
+            .map(|e| e.command.as_str())
+            .collect()
+    }
+
+    fn make_model() -> Model {
+        let mut entries = vec![];
+        for description in ["a", "b", "c"] {
+            let mut entry_group = make_test_entry_group();
+            entry_group.description = String::from(description);
+            entries.push(entry_group);
+        }
+        entries.push(EntryGroup::new_read_only(String::from("make"), vec![]));
+        Model {
+            entries,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn move_groups() {
+        let mut model = make_model();
+
+        model.move_selected(true);
+        assert_eq!(descriptions(&model), ["b", "a", "c", "make"]);
+        assert_eq!(model.idx_entrygroup, 1);
+
+        // Virtual groups stay in place
+        model.move_selected(true);
+        model.move_selected(true);
+        assert_eq!(descriptions(&model), ["b", "c", "a", "make"]);
+        assert_eq!(model.idx_entrygroup, 2);
+
+        // Pinned groups are only moved among them
+        model.entries[0].pinned = true;
+        model.idx_entrygroup = 0;
+        model.move_selected(true);
+        assert_eq!(descriptions(&model), ["b", "c", "a", "make"]);
+
+        model.undo();
+        assert_eq!(descriptions(&model), ["b", "a", "c", "make"]);
+        assert_eq!(model.idx_entrygroup, 1);
+    }
+
+    #[test]
+    fn move_groups_with_their_children() {
+        let mut model = make_model();
+        model.entries[1].depth = 1;
+        model.entries[2].depth = 1;
+
+        // "b" and "c" are children of "a", and only swap among them
+        model.idx_entrygroup = 2;
+        model.move_selected(true);
+        assert_eq!(descriptions(&model), ["a", "b", "c", "make"]);
+        model.move_selected(false);
+        assert_eq!(descriptions(&model), ["a", "c", "b", "make"]);
+        assert_eq!(model.idx_entrygroup, 1);
+
+        // "a" moves with its children
+        model.entries.insert(0, make_test_entry_group());
+        model.idx_entrygroup = 0;
+        model.move_selected(true);
+        assert_eq!(descriptions(&model), ["a", "c", "b", "description", "make"]);
+        assert_eq!(model.idx_entrygroup, 3);
+        model.undo();
+        assert_eq!(descriptions(&model), ["description", "a", "c", "b", "make"]);
+    }
+
+    #[test]
+    fn move_entries_only_in_manual_order() {
+        let mut model = make_model();
+        model.current_screen = CurrentScreen::Secondary;
+
+        model.move_selected(true);
+        assert_eq!(commands(&model, 0), ["command2", "command1"]);
+        assert_eq!(model.idx_entry, 1);
+        model.move_selected(false);
+        assert_eq!(commands(&model, 0), ["command1", "command2"]);
+        assert_eq!(model.idx_entry, 0);
+
+        model.entry_sort = SortMode::Alphabetical;
+        model.move_selected(true);
+        assert_eq!(commands(&model, 0), ["command1", "command2"]);
+        assert!(model.status.is_some());
+    }
+
+    #[test]
+    fn cut_and_paste_entries() {
+        let mut model = make_model();
+        model.current_screen = CurrentScreen::Secondary;
+
+        // Nothing moves until pasted
+        model.cut_entry();
+        assert_eq!(commands(&model, 0), ["command1", "command2"]);
+
+        model.idx_entrygroup = 2;
+        model.paste_entry();
+        assert_eq!(commands(&model, 0), ["command2"]);
+        assert_eq!(commands(&model, 2), ["command1", "command1", "command2"]);
+        assert_eq!((model.idx_entrygroup, model.idx_entry), (2, 1));
+        model.paste_entry();
+        assert_eq!(commands(&model, 2), ["command1", "command1", "command2"]);
+
+        model.undo();
+        assert_eq!(commands(&model, 0), ["command1", "command2"]);
+        assert_eq!(commands(&model, 2), ["command1", "command2"]);
+
+        // Within the same group
+        model.idx_entrygroup = 1;
+        model.idx_entry = 0;
+        model.cut_entry();
+        model.idx_entry = 1;
+        model.paste_entry();
+        assert_eq!(commands(&model, 1), ["command2", "command1"]);
+
+        // Read-only groups are left untouched
+        model.cut_entry();
+        model.idx_entrygroup = 3;
+        model.paste_entry();
+        assert!(model.entries[3].entries.is_empty());
+    }
+
+    #[test]
+    fn duplicate_entries() {
+        let mut model = make_model();
+        model.current_screen = CurrentScreen::Secondary;
+        model.entries[0].entries[0].pinned = true;
+
+        model.duplicate_entry();
+        assert_eq!(commands(&model, 0), ["command1", "command1", "command2"]);
+        assert!(!model.entries[0].entries[1].pinned);
+        assert_eq!(model.idx_entry, 1);
+    }
+}
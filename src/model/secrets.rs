@@ -0,0 +1,379 @@
+//! Secret values of the `<placeholder>`s of the commands, e.g. tokens. They are stored
+//! encrypted with a key derived from a passphrase, asked the first time they are needed,
+//! and masked until revealed
+
+use crate::config::SECRET_MASK_STRING;
+use crate::entry::Entry;
+use crate::model::crypto::{salt_of, SecretKey};
+use crate::model::history::Edit;
+use crate::model::prompt::{Prompt, PromptKind};
+use crate::model::Model;
+use base64::prelude::{Engine, BASE64_STANDARD};
+use std::borrow::Cow;
+use std::ops::Range;
+
+/// Action waiting for a passphrase, of the secrets or of the database
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SecretAction {
+    /// Load the encrypted database
+    OpenDatabase,
+    /// Show the secret values instead of masking them
+    Reveal,
+    /// Copy the selected or marked commands, with their secret values
+    Copy,
+    /// Copy the nth pinned entry, with its secret values
+    CopyPinned(usize),
+    /// Keep a placeholder of the selected command secret
+    Store,
+}
+
+/// Names of the `<placeholder>`s of a command, in order, each one once
+pub fn placeholders(command: &str) -> Vec<&str> {
+    let mut names: Vec<&str> = vec![];
+    for (_, name) in placeholder_spans(command) {
+        if !names.contains(&name) {
+            names.push(name);
+        }
+    }
+    names
+}
+
+/// Byte ranges of the `<placeholder>`s of a command, with their names. The placeholders are
+/// found the same way everywhere: highlighted, asked as secrets and checked by the linter
+pub fn placeholder_spans(command: &str) -> Vec<(Range<usize>, &str)> {
+    command
+        .match_indices('<')
+        .filter_map(|(start, _)| {
+            let name = placeholder_at(&command[start..])?;
+            Some((start..start + name.len() + 2, name))
+        })
+        .collect()
+}
+
+/// Name of the placeholder starting the text, e.g. `file` for `<file> backup/`
+pub fn placeholder_at(text: &str) -> Option<&str> {
+    let rest = text.strip_prefix('<')?;
+    let end = rest.find(|c: char| c == '>' || c == '<' || c.is_whitespace())?;
+    (end > 0 && rest[end..].starts_with('>')).then(|| &rest[..end])
+}
+
+impl Model {
+    /// Whether the secrets can be decrypted
+    pub fn secrets_unlocked(&self) -> bool {
+        self.secret_key.is_some()
+    }
+
+    /// An encrypted value of the database, if any
+    fn any_secret(&self) -> Option<&str> {
+        self.entries
+            .iter()
+            .flat_map(|entry_group| &entry_group.entries)
+            .flat_map(|entry| entry.secrets.values())
+            .map(String::as_str)
+            .next()
+    }
+
+    /// Derive the key of the secrets from the passphrase. Every secret shares the same salt,
+    /// and the passphrase is wrong if it cannot decrypt them
+    pub fn unlock_secrets(&mut self, passphrase: &str) -> bool {
+        let secret = self
+            .any_secret()
+            .and_then(|blob| BASE64_STANDARD.decode(blob).ok());
+        let key = match &secret {
+            Some(data) => match salt_of(data) {
+                Ok(salt) => SecretKey::derive(passphrase, salt),
+                Err(_) => return false,
+            },
+            None => SecretKey::new(passphrase),
+        };
+        if let Some(data) = &secret {
+            if key.decrypt(data).is_err() {
+                tracing::warn!("wrong passphrase of the secrets");
+                return false;
+            }
+        }
+        self.secret_key = Some(key);
+        true
+    }
+
+    /// Whether the secrets are unlocked. Otherwise the passphrase is asked, to do the action
+    /// once typed
+    pub fn unlock_for(&mut self, action: SecretAction) -> bool {
+        if self.secrets_unlocked() {
+            return true;
+        }
+        self.prompt = Some(Prompt::new(PromptKind::Passphrase(action)));
+        false
+    }
+
+    /// Decrypted value of a secret, if unlocked
+    fn decrypt_secret(&self, blob: &str) -> Option<String> {
+        let data = BASE64_STANDARD.decode(blob).ok()?;
+        let value = self.secret_key.as_ref()?.decrypt(&data).ok()?;
+        String::from_utf8(value).ok()
+    }
+
+    /// Text of an entry as rendered: its secret placeholders are masked, or replaced by
+    /// their values once revealed
+    pub fn shown<'a>(&self, entry: &Entry, text: &'a str) -> Cow<'a, str> {
+        let mut shown = Cow::Borrowed(text);
+        for (name, blob) in &entry.secrets {
+            let placeholder = format!("<{}>", name);
+            if !shown.contains(&placeholder) {
+                continue;
+            }
+            let value = match self.reveal_secrets {
+                true => self.decrypt_secret(blob),
+                false => None,
+            }
+            .unwrap_or_else(|| format!("<{}:{}>", name, SECRET_MASK_STRING));
+            shown = Cow::Owned(shown.replace(&placeholder, &value));
+        }
+        shown
+    }
+
+    /// Command with the values of its secret placeholders, if unlocked
+    fn with_secrets(&self, command: &str) -> String {
+        let entry = self
+            .entries
+            .iter()
+            .flat_map(|entry_group| &entry_group.entries)
+            .find(|entry| entry.command == command && !entry.secrets.is_empty());
+        let Some(entry) = entry else {
+            return command.to_string();
+        };
+        entry
+            .secrets
+            .iter()
+            .fold(command.to_string(), |command, (name, blob)| {
+                match self.decrypt_secret(blob) {
+                    Some(value) => command.replace(&format!("<{}>", name), &value),
+                    None => command,
+                }
+            })
+    }
+
+    /// Whether the copied commands have secret placeholders
+    pub fn copies_secrets(&self) -> bool {
+        self.copied_commands()
+            .iter()
+            .any(|&command| self.has_secret(command))
+    }
+
+    /// Whether a secret is stored for a placeholder of the command
+    fn has_secret(&self, command: &str) -> bool {
+        self.entries
+            .iter()
+            .flat_map(|entry_group| &entry_group.entries)
+            .any(|entry| entry.command == command && !entry.secrets.is_empty())
+    }
+
+    /// Copied text with the values of the secrets, if unlocked
+    pub fn copied_text_with_secrets(&self) -> Option<String> {
+        self.copied_text()?;
+        let commands: Vec<String> = self
+            .copied_commands()
+            .into_iter()
+            .map(|command| self.with_secrets(command))
+            .collect();
+        Some(commands.join(self.separator.as_str()))
+    }
+
+    /// Reveal or mask the secret values, asking the passphrase first if needed
+    pub fn toggle_reveal(&mut self) {
+        if self.any_secret().is_none() {
+            self.status = Some(String::from("No secret is stored"));
+            return;
+        }
+        if !self.unlock_for(SecretAction::Reveal) {
+            return;
+        }
+        self.reveal_secrets = !self.reveal_secrets;
+        self.status = Some(String::from(match self.reveal_secrets {
+            true => "Secrets revealed",
+            false => "Secrets masked",
+        }));
+    }
+
+    /// Ask which placeholder of the selected command to keep secret, then its value
+    pub fn start_storing_secret(&mut self) {
+        let entry = &self.entries[self.idx_entrygroup].entries[self.idx_entry];
+        let names = placeholders(&entry.command);
+        let Some(&first) = names
+            .iter()
+            .find(|name| !entry.secrets.contains_key(**name))
+            .or(names.first())
+        else {
+            self.status = Some(String::from(
+                "The command has no <placeholder> to keep secret",
+            ));
+            return;
+        };
+        let name = first.to_string();
+
+        // Entries that are not stored (e.g. project tasks) would lose their secrets
+        if !self.is_stored(&entry.command) {
+            self.status = Some(String::from("Only the stored commands can keep secrets"));
+            return;
+        }
+        if !self.unlock_for(SecretAction::Store) {
+            return;
+        }
+        let mut prompt = Prompt::new(PromptKind::SecretName);
+        prompt.input = name;
+        self.prompt = Some(prompt);
+    }
+
+    /// Store the encrypted value of a placeholder of the selected command, or forget it if
+    /// empty. Every copy of the entry is updated, as for pinning
+    pub(crate) fn store_secret(&mut self, name: &str, value: &str) {
+        let Some(key) = &self.secret_key else {
+            return;
+        };
+        let blob =
+            (!value.is_empty()).then(|| BASE64_STANDARD.encode(key.encrypt(value.as_bytes())));
+
+        let verb = match blob {
+            Some(_) => "keep",
+            None => "forget",
+        };
+        self.edit_every_copy(format!("{} <{}> secret", verb, name), |entry| match &blob {
+            Some(blob) => {
+                entry.secrets.insert(name.to_string(), blob.clone());
+            }
+            None => {
+                entry.secrets.remove(name);
+            }
+        });
+    }
+
+    /// Whether the command is saved in the database, i.e. not only in read-only groups
+    pub(crate) fn is_stored(&self, command: &str) -> bool {
+        self.entries
+            .iter()
+            .filter(|entry_group| !entry_group.read_only)
+            .any(|entry_group| entry_group.entries.iter().any(|e| e.command == command))
+    }
+
+    /// Change every copy of the selected entry in a single undoable edit, as the entries of
+    /// virtual groups are copies. The selected one goes last so that undoing selects it back
+    pub(crate) fn edit_every_copy(&mut self, description: String, change: impl Fn(&mut Entry)) {
+        let command = self.entries[self.idx_entrygroup].entries[self.idx_entry]
+            .command
+            .clone();
+        let mut edits: Vec<Edit> = vec![];
+        for (idx_entrygroup, entry_group) in self.entries.iter().enumerate() {
+            for (idx_entry, entry) in entry_group.entries.iter().enumerate() {
+                if entry.command == command {
+                    let mut after = entry.clone();
+                    change(&mut after);
+                    edits.push(Edit::ModifyEntry {
+                        group: idx_entrygroup,
+                        idx: idx_entry,
+                        before: entry.clone(),
+                        after,
+                    });
+                }
+            }
+        }
+        let selected = (self.idx_entrygroup, self.idx_entry);
+        let is_selected = |edit: &Edit| match edit {
+            Edit::ModifyEntry { group, idx, .. } => (*group, *idx) == selected,
+            _ => false,
+        };
+        edits.sort_by_key(is_selected);
+        self.edit(description, edits);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::tests::make_test_entry_group;
+    use crate::model::CurrentScreen;
+
+    fn model_with_placeholder() -> Model {
+        let mut model = Model {
+            entries: vec![make_test_entry_group()],
+            current_screen: CurrentScreen::Secondary,
+            ..Default::default()
+        };
+        model.entries[0].entries[0].command = String::from("curl -H 'Bearer <token>' <url>");
+        model
+    }
+
+    #[test]
+    fn find_placeholders() {
+        assert_eq!(
+            placeholders("scp <file> <user>@<host>:<file>"),
+            ["file", "user", "host"]
+        );
+        assert!(placeholders("echo a<b <> <c d> x > y").is_empty());
+        assert_eq!(placeholder_spans("cp <a> <b>"), [(3..6, "a"), (7..10, "b")]);
+    }
+
+    #[test]
+    fn store_and_reveal_secrets() {
+        let mut model = model_with_placeholder();
+        model.start_storing_secret();
+        assert_eq!(
+            model.prompt.as_ref().map(|prompt| &prompt.kind),
+            Some(&PromptKind::Passphrase(SecretAction::Store))
+        );
+        assert!(model.unlock_secrets("correct horse"));
+        model.store_secret("token", "hunter2");
+
+        let entry = model.entries[0].entries[0].clone();
+        assert!(!entry.secrets["token"].contains("hunter2"));
+        assert_eq!(
+            model.shown(&entry, &entry.command),
+            format!("curl -H 'Bearer <token:{}>' <url>", SECRET_MASK_STRING)
+        );
+        model.reveal_secrets = true;
+        assert_eq!(
+            model.shown(&entry, &entry.command),
+            "curl -H 'Bearer hunter2' <url>"
+        );
+        assert!(model.copies_secrets());
+        assert_eq!(
+            model.copied_text_with_secrets().as_deref(),
+            Some("curl -H 'Bearer hunter2' <url>")
+        );
+
+        // Locked again, e.g. in the next session, only the right passphrase decrypts them
+        model.secret_key = None;
+        assert_eq!(
+            model.copied_text_with_secrets().as_deref(),
+            Some("curl -H 'Bearer <token>' <url>")
+        );
+        assert!(!model.unlock_secrets("battery staple"));
+        assert!(model.unlock_secrets("correct horse"));
+        assert!(model
+            .copied_text_with_secrets()
+            .unwrap()
+            .contains("hunter2"));
+
+        // Other commands are not affected
+        model.idx_entry = 1;
+        assert!(!model.copies_secrets());
+
+        // Storing is undoable, and an empty value forgets the secret
+        model.idx_entry = 0;
+        model.store_secret("token", "");
+        assert!(model.entries[0].entries[0].secrets.is_empty());
+        model.undo();
+        assert!(model.entries[0].entries[0].secrets.contains_key("token"));
+    }
+
+    #[test]
+    fn store_needs_a_placeholder() {
+        let mut model = model_with_placeholder();
+        model.idx_entry = 1;
+        model.start_storing_secret();
+        assert!(model.prompt.is_none());
+        assert!(model.status.is_some());
+
+        model.toggle_reveal();
+        assert_eq!(model.status.as_deref(), Some("No secret is stored"));
+    }
+}
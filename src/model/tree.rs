@@ -0,0 +1,232 @@
+use crate::entry::EntryGroup;
+use crate::model::Model;
+
+/// Turn a tree of groups into the flat list kept by the model: every group is followed
+/// by its descendants, and knows its depth
+pub(crate) fn flatten(groups: Vec<EntryGroup>) -> Vec<EntryGroup> {
+    fn push(flat: &mut Vec<EntryGroup>, mut group: EntryGroup, depth: usize) {
+        let children = std::mem::take(&mut group.groups);
+        group.depth = depth;
+        flat.push(group);
+        for child in children {
+            push(flat, child, depth + 1);
+        }
+    }
+
+    let mut flat = vec![];
+    for group in groups {
+        push(&mut flat, group, 0);
+    }
+    flat
+}
+
+/// Build back the tree of groups from the flat list kept by the model
+pub(crate) fn unflatten(flat: Vec<EntryGroup>) -> Vec<EntryGroup> {
+    // Groups whose children are still being collected, from the root to the deepest one
+    let mut path: Vec<EntryGroup> = vec![];
+    let mut roots = vec![];
+
+    let close = |path: &mut Vec<EntryGroup>, roots: &mut Vec<EntryGroup>| {
+        let group = path.pop().unwrap();
+        match path.last_mut() {
+            Some(parent) => parent.groups.push(group),
+            None => roots.push(group),
+        }
+    };
+
+    for group in flat {
+        while path.len() > group.depth {
+            close(&mut path, &mut roots);
+        }
+        path.push(group);
+    }
+    while !path.is_empty() {
+        close(&mut path, &mut roots);
+    }
+    roots
+}
+
+impl Model {
+    /// Number of groups in the subtree of the group `idx`, itself included
+    pub fn subtree_len(&self, idx: usize) -> usize {
+        let depth = self.entries[idx].depth;
+        1 + self.entries[idx + 1..]
+            .iter()
+            .take_while(|group| group.depth > depth)
+            .count()
+    }
+
+    /// Parent of the group `idx`, if it is not a top-level one
+    pub fn parent_of(&self, idx: usize) -> Option<usize> {
+        let depth = self.entries[idx].depth;
+        self.entries[..idx]
+            .iter()
+            .rposition(|group| group.depth < depth)
+    }
+
+    /// Children of the group `parent`, or top-level groups, in the model order
+    pub fn child_groups(&self, parent: Option<usize>) -> Vec<usize> {
+        let (range, depth) = match parent {
+            Some(idx) => (
+                idx + 1..idx + self.subtree_len(idx),
+                self.entries[idx].depth + 1,
+            ),
+            None => (0..self.entries.len(), 0),
+        };
+        range.filter(|&i| self.entries[i].depth == depth).collect()
+    }
+
+    pub fn has_children(&self, idx: usize) -> bool {
+        self.entries
+            .get(idx + 1)
+            .is_some_and(|group| group.depth > self.entries[idx].depth)
+    }
+
+    /// Whether the group `idx` is inside a collapsed group
+    pub fn is_hidden(&self, idx: usize) -> bool {
+        let mut parent = self.parent_of(idx);
+        while let Some(p) = parent {
+            if self.entries[p].collapsed {
+                return true;
+            }
+            parent = self.parent_of(p);
+        }
+        false
+    }
+
+    /// Indices of the groups listed in the main screen, i.e. not inside a collapsed group
+    pub fn visible_group_order(&self) -> Vec<usize> {
+        self.group_order()
+            .into_iter()
+            .filter(|&idx| !self.is_hidden(idx))
+            .collect()
+    }
+
+    /// Descriptions of the selected group and its ancestors, from the root. None without
+    /// groups
+    pub fn breadcrumb(&self) -> Vec<&str> {
+        let Some(entry_group) = self.entries.get(self.idx_entrygroup) else {
+            return vec![];
+        };
+        let mut path = vec![entry_group.description.as_str()];
+        let mut parent = self.parent_of(self.idx_entrygroup);
+        while let Some(p) = parent {
+            path.insert(0, self.entries[p].description.as_str());
+            parent = self.parent_of(p);
+        }
+        path
+    }
+
+    /// Show the child groups of the selected group
+    pub fn expand(&mut self) {
+        if let Some(entry_group) = self.entries.get_mut(self.idx_entrygroup) {
+            entry_group.collapsed = false;
+        }
+    }
+
+    /// Hide the child groups of the selected group, or select its parent
+    /// if there is nothing to hide
+    pub fn collapse(&mut self) {
+        let idx = self.idx_entrygroup;
+        if idx >= self.entries.len() {
+            return;
+        }
+        if self.has_children(idx) && !self.entries[idx].collapsed {
+            self.entries[idx].collapsed = true;
+        } else if let Some(parent) = self.parent_of(idx) {
+            self.idx_entrygroup = parent;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::usage::SortMode;
+
+    fn group(description: &str, groups: Vec<EntryGroup>) -> EntryGroup {
+        EntryGroup {
+            groups,
+            ..EntryGroup::new(String::from(description), vec![])
+        }
+    }
+
+    fn make_tree() -> Vec<EntryGroup> {
+        vec![
+            group(
+                "Git",
+                vec![
+                    group("Rebasing", vec![]),
+                    group("Branching", vec![group("Remote", vec![])]),
+                ],
+            ),
+            group("Cargo", vec![]),
+        ]
+    }
+
+    fn descriptions(model: &Model, order: Vec<usize>) -> Vec<&str> {
+        order
+            .into_iter()
+            .map(|i| model.entries[i].description.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn flatten_and_unflatten() {
+        let flat = flatten(make_tree());
+        let depths: Vec<usize> = flat.iter().map(|group| group.depth).collect();
+        assert_eq!(depths, [0, 1, 1, 2, 0]);
+        assert!(flat.iter().all(|group| group.groups.is_empty()));
+
+        let tree = unflatten(flat);
+        let reset_depth = |tree: Vec<EntryGroup>| flatten(unflatten(flatten(tree)));
+        assert_eq!(reset_depth(tree.clone()), reset_depth(make_tree()));
+        assert_eq!(tree[0].groups[1].groups[0].description, "Remote");
+        assert_eq!(tree[1].description, "Cargo");
+    }
+
+    #[test]
+    fn navigate_tree() {
+        let mut model = Model {
+            entries: flatten(make_tree()),
+            ..Default::default()
+        };
+
+        assert_eq!(model.subtree_len(0), 4);
+        assert_eq!(model.subtree_len(2), 2);
+        assert_eq!(model.parent_of(3), Some(2));
+        assert_eq!(model.parent_of(4), None);
+        assert_eq!(model.child_groups(Some(0)), [1, 2]);
+        assert_eq!(model.child_groups(None), [0, 4]);
+
+        model.idx_entrygroup = 3;
+        assert_eq!(model.breadcrumb(), ["Git", "Branching", "Remote"]);
+        assert!(Model::default().breadcrumb().is_empty());
+    }
+
+    #[test]
+    fn sort_siblings_and_collapse() {
+        let mut model = Model {
+            entries: flatten(make_tree()),
+            group_sort: SortMode::Alphabetical,
+            ..Default::default()
+        };
+        let order = model.group_order();
+        assert_eq!(
+            descriptions(&model, order),
+            ["Cargo", "Git", "Branching", "Remote", "Rebasing"]
+        );
+
+        model.idx_entrygroup = 0;
+        model.collapse();
+        assert!(model.entries[0].collapsed);
+        let order = model.visible_group_order();
+        assert_eq!(descriptions(&model, order), ["Cargo", "Git"]);
+
+        // Collapsing a leaf selects its parent
+        model.expand();
+        model.idx_entrygroup = 1;
+        model.collapse();
+        assert_eq!(model.idx_entrygroup, 0);
+    }
+}
@@ -0,0 +1,355 @@
+//! Usage statistics of the commands, and the orders they allow
+
+use crate::config::{RECENT_GROUP_SIZE, RECENT_GROUP_TITLE};
+use crate::entry::{Entry, EntryGroup};
+use crate::model::{CurrentScreen, Model};
+use serde::{Deserialize, Serialize};
+use std::cmp::Reverse;
+use std::fmt;
+use std::str::FromStr;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Usage statistics of a command, persisted in the stats file next to the cache file
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UsageStats {
+    /// Number of times the command was selected
+    pub count: u32,
+    /// Last time the command was selected, in seconds since the UNIX epoch
+    pub last_used: u64,
+}
+
+impl UsageStats {
+    /// Score mixing how often and how recently the command was used
+    pub fn frecency(&self, now: u64) -> u64 {
+        const DAY: u64 = 24 * 60 * 60;
+        let weight = match now.saturating_sub(self.last_used) / DAY {
+            0..=3 => 100,
+            4..=13 => 70,
+            14..=30 => 50,
+            31..=89 => 30,
+            _ => 10,
+        };
+        self.count as u64 * weight
+    }
+}
+
+/// Order in which groups or entries are listed
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum SortMode {
+    /// Order of the database file
+    #[default]
+    Manual,
+    Alphabetical,
+    MostUsed,
+    MostRecent,
+    Frecency,
+}
+
+impl SortMode {
+    /// Next mode, cycling back to the first one
+    pub fn next(self) -> SortMode {
+        match self {
+            SortMode::Manual => SortMode::Alphabetical,
+            SortMode::Alphabetical => SortMode::MostUsed,
+            SortMode::MostUsed => SortMode::MostRecent,
+            SortMode::MostRecent => SortMode::Frecency,
+            SortMode::Frecency => SortMode::Manual,
+        }
+    }
+}
+
+impl fmt::Display for SortMode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            SortMode::Manual => "manual",
+            SortMode::Alphabetical => "alphabetical",
+            SortMode::MostUsed => "most-used",
+            SortMode::MostRecent => "most-recent",
+            SortMode::Frecency => "frecency",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl FromStr for SortMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "manual" => Ok(SortMode::Manual),
+            "alphabetical" => Ok(SortMode::Alphabetical),
+            "most-used" => Ok(SortMode::MostUsed),
+            "most-recent" => Ok(SortMode::MostRecent),
+            "frecency" => Ok(SortMode::Frecency),
+            _ => Err(format!(
+                "unknown sort mode '{}' (manual, alphabetical, most-used, most-recent, frecency)",
+                s
+            )),
+        }
+    }
+}
+
+/// Current time in seconds since the UNIX epoch
+pub(crate) fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+impl Model {
+    /// Usage statistics of a command, empty if it was never used
+    pub fn stats_of(&self, command: &str) -> UsageStats {
+        self.stats.get(command).cloned().unwrap_or_default()
+    }
+
+    /// Record that the selected entry, or the marked ones, have been used right now
+    pub fn record_usage(&mut self) {
+        let commands = match self.marked.is_empty() {
+            true => vec![self.entries[self.idx_entrygroup].entries[self.idx_entry]
+                .command
+                .clone()],
+            false => self.marked.clone(),
+        };
+        for command in commands {
+            let stats = self.stats.entry(command).or_default();
+            stats.count += 1;
+            stats.last_used = now();
+        }
+    }
+
+    /// Switch to the next sort mode of the list shown in the current screen
+    pub fn cycle_sort_mode(&mut self) {
+        match self.current_screen {
+            CurrentScreen::Main => self.group_sort = self.group_sort.next(),
+            CurrentScreen::Secondary => self.entry_sort = self.entry_sort.next(),
+        }
+    }
+
+    /// Indices of the entry groups in the order they are shown, child groups right after
+    /// their parent. The "Recent" group, if any, always stays at the top, and the pinned
+    /// groups go before their siblings
+    pub fn group_order(&self) -> Vec<usize> {
+        let skip = self.has_recent_group as usize;
+        let now = now();
+
+        // Entries of a group and its descendants
+        let subtree_entries = |idx: usize| {
+            self.entries[idx..idx + self.subtree_len(idx)]
+                .iter()
+                .flat_map(|group| group.entries.iter())
+        };
+        let group_stats = |idx: usize| -> UsageStats {
+            subtree_entries(idx)
+                .map(|entry| self.stats_of(&entry.command))
+                .fold(UsageStats::default(), |acc, s| UsageStats {
+                    count: acc.count + s.count,
+                    last_used: acc.last_used.max(s.last_used),
+                })
+        };
+        let group_frecency = |idx: usize| -> u64 {
+            subtree_entries(idx)
+                .map(|entry| self.stats_of(&entry.command).frecency(now))
+                .sum()
+        };
+
+        // Sort the children of every group among them
+        let sorted = |mut siblings: Vec<usize>| -> Vec<usize> {
+            match self.group_sort {
+                SortMode::Manual => (),
+                SortMode::Alphabetical => {
+                    siblings.sort_by_cached_key(|&i| self.entries[i].description.to_lowercase())
+                }
+                SortMode::MostUsed => {
+                    siblings.sort_by_cached_key(|&i| Reverse(group_stats(i).count))
+                }
+                SortMode::MostRecent => {
+                    siblings.sort_by_cached_key(|&i| Reverse(group_stats(i).last_used))
+                }
+                SortMode::Frecency => siblings.sort_by_cached_key(|&i| Reverse(group_frecency(i))),
+            }
+            siblings.sort_by_key(|&i| !self.entries[i].pinned);
+            siblings
+        };
+
+        let mut order: Vec<usize> = (0..skip).collect();
+        let roots = self.child_groups(None).into_iter().filter(|&i| i >= skip);
+        let mut stack: Vec<usize> = sorted(roots.collect());
+        stack.reverse();
+        while let Some(idx) = stack.pop() {
+            order.push(idx);
+            stack.extend(sorted(self.child_groups(Some(idx))).into_iter().rev());
+        }
+        order
+    }
+
+    /// Indices of the entries of the selected group in the order they are shown, without
+    /// the hidden ones. None without groups
+    pub fn entry_order(&self) -> Vec<usize> {
+        let Some(entry_group) = self.entries.get(self.idx_entrygroup) else {
+            return vec![];
+        };
+        let entries = &entry_group.entries;
+        let mut order: Vec<usize> = (0..entries.len())
+            .filter(|&i| !(self.hide_missing && self.is_missing(&entries[i].command)))
+            .collect();
+        let now = now();
+
+        match self.entry_sort {
+            SortMode::Manual => (),
+            SortMode::Alphabetical => {
+                order.sort_by_cached_key(|&i| entries[i].command.to_lowercase())
+            }
+            SortMode::MostUsed => {
+                order.sort_by_cached_key(|&i| Reverse(self.stats_of(&entries[i].command).count))
+            }
+            SortMode::MostRecent => {
+                order.sort_by_cached_key(|&i| Reverse(self.stats_of(&entries[i].command).last_used))
+            }
+            SortMode::Frecency => order
+                .sort_by_cached_key(|&i| Reverse(self.stats_of(&entries[i].command).frecency(now))),
+        }
+
+        order
+    }
+
+    /// Add a virtual group at the top with the most recently used commands
+    pub fn add_recent_group(&mut self) {
+        let mut recent: Vec<&Entry> = vec![];
+        for entry in self.entries.iter().flat_map(|eg| eg.entries.iter()) {
+            if self.stats.contains_key(&entry.command)
+                && !recent.iter().any(|e| e.command == entry.command)
+            {
+                recent.push(entry);
+            }
+        }
+
+        if recent.is_empty() {
+            return;
+        }
+
+        recent.sort_by_key(|entry| Reverse(self.stats_of(&entry.command).last_used));
+        let recent: Vec<Entry> = recent
+            .into_iter()
+            .take(RECENT_GROUP_SIZE)
+            .cloned()
+            .collect();
+
+        self.entries.insert(
+            0,
+            EntryGroup::new_read_only(RECENT_GROUP_TITLE.to_string(), recent),
+        );
+        self.has_recent_group = true;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::tests::make_test_entry_group;
+
+    const DAY: u64 = 24 * 60 * 60;
+
+    #[test]
+    fn frecency_decays_with_age() {
+        let now = 1000 * DAY;
+        let recent = UsageStats {
+            count: 2,
+            last_used: now - DAY,
+        };
+        let old = UsageStats {
+            count: 2,
+            last_used: now - 100 * DAY,
+        };
+        assert_eq!(recent.frecency(now), 200);
+        assert_eq!(old.frecency(now), 20);
+    }
+
+    #[test]
+    fn parse_sort_mode() {
+        let mut mode = SortMode::default();
+        for _ in 0..5 {
+            assert_eq!(mode.to_string().parse::<SortMode>(), Ok(mode));
+            mode = mode.next();
+        }
+        assert_eq!(mode, SortMode::Manual);
+        assert!("unknown".parse::<SortMode>().is_err());
+    }
+
+    #[test]
+    fn record_usage_of_selected_entry() {
+        let mut model = Model {
+            entries: vec![make_test_entry_group()],
+            idx_entry: 1,
+            ..Default::default()
+        };
+
+        model.record_usage();
+        model.record_usage();
+
+        assert_eq!(model.stats_of("command2").count, 2);
+        assert!(model.stats_of("command2").last_used > 0);
+        assert_eq!(model.stats_of("command1"), UsageStats::default());
+    }
+
+    #[test]
+    fn sort_entries() {
+        let mut model = Model::default();
+        let mut entrygroup = make_test_entry_group();
+        entrygroup.entries[0].command = String::from("zzz");
+        model.entries = vec![entrygroup];
+        model.stats.insert(
+            String::from("zzz"),
+            UsageStats {
+                count: 1,
+                last_used: now(),
+            },
+        );
+        model.stats.insert(
+            String::from("command2"),
+            UsageStats {
+                count: 5,
+                last_used: now() - 100 * DAY,
+            },
+        );
+
+        assert_eq!(model.entry_order(), vec![0, 1]);
+        model.entry_sort = SortMode::Alphabetical;
+        assert_eq!(model.entry_order(), vec![1, 0]);
+        model.entry_sort = SortMode::MostUsed;
+        assert_eq!(model.entry_order(), vec![1, 0]);
+        model.entry_sort = SortMode::MostRecent;
+        assert_eq!(model.entry_order(), vec![0, 1]);
+        model.entry_sort = SortMode::Frecency;
+        assert_eq!(model.entry_order(), vec![0, 1]);
+
+        model.entries.clear();
+        assert!(model.entry_order().is_empty());
+    }
+
+    #[test]
+    fn recent_group_stays_on_top() {
+        let mut model = Model::default();
+        let mut other = make_test_entry_group();
+        other.description = String::from("another");
+        model.entries = vec![make_test_entry_group(), other];
+        model.stats.insert(
+            String::from("command2"),
+            UsageStats {
+                count: 1,
+                last_used: now(),
+            },
+        );
+
+        model.add_recent_group();
+
+        assert_eq!(model.entries.len(), 3);
+        assert_eq!(model.entries[0].description, RECENT_GROUP_TITLE);
+        assert!(model.entries[0].read_only);
+        assert_eq!(model.entries[0].entries.len(), 1);
+        assert_eq!(model.entries[0].entries[0].command, "command2");
+
+        model.group_sort = SortMode::Alphabetical;
+        assert_eq!(model.group_order(), vec![0, 2, 1]);
+    }
+}
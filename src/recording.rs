@@ -0,0 +1,108 @@
+use crate::message::Message;
+use crossterm::event::Event;
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, ErrorKind, Write};
+use std::path::Path;
+use std::time::Instant;
+
+/// Input event of a session and the message it was mapped to, as written by `--record`
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct Record {
+    /// Time since the start of the session
+    pub millis: u64,
+    /// None for the messages sent without any input, i.e. Init
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub event: Option<Event>,
+    /// None for the events without any action, e.g. unbound keys
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<Message>,
+}
+
+/// Writes the records of a session to a file, one JSON object per line. Every record is
+/// written right away, so that the file is complete even if the application crashes
+pub struct Recorder {
+    file: File,
+    start: Instant,
+}
+
+impl Recorder {
+    pub fn create(path: &Path) -> io::Result<Recorder> {
+        Ok(Recorder {
+            file: File::create(path)?,
+            start: Instant::now(),
+        })
+    }
+
+    pub fn record(&mut self, event: Option<Event>, message: Option<Message>) -> io::Result<()> {
+        let record = Record {
+            millis: self.start.elapsed().as_millis() as u64,
+            event,
+            message,
+        };
+        writeln!(self.file, "{}", serde_json::to_string(&record)?)
+    }
+}
+
+/// Read the records of a session written by a `Recorder`
+pub fn load_records(path: &Path) -> io::Result<Vec<Record>> {
+    let mut records = vec![];
+    for (n, line) in BufReader::new(File::open(path)?).lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let record = serde_json::from_str(&line).map_err(|e| {
+            io::Error::new(
+                ErrorKind::InvalidData,
+                format!("{}:{}: {}", path.display(), n + 1, e),
+            )
+        })?;
+        records.push(record);
+    }
+    Ok(records)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::Panel;
+    use crate::ui::Hit;
+    use crossterm::event::{KeyCode, KeyEvent};
+
+    #[test]
+    fn record_and_load_sessions() -> io::Result<()> {
+        let dir = std::env::temp_dir().join("memors-record-and-load");
+        std::fs::create_dir_all(&dir)?;
+        let path = dir.join("session.jsonl");
+
+        let mut recorder = Recorder::create(&path)?;
+        recorder.record(None, Some(Message::Init))?;
+        recorder.record(Some(Event::Resize(80, 24)), None)?;
+        recorder.record(
+            Some(Event::Key(KeyEvent::from(KeyCode::Down))),
+            Some(Message::NextEntry),
+        )?;
+        recorder.record(None, Some(Message::Click(Hit::Panel(Panel::Description))))?;
+
+        let records = load_records(&path)?;
+        assert_eq!(records.len(), 4);
+        assert_eq!(records[1].event, Some(Event::Resize(80, 24)));
+        assert_eq!(records[2].message, Some(Message::NextEntry));
+        assert_eq!(
+            records[3].message,
+            Some(Message::Click(Hit::Panel(Panel::Description)))
+        );
+        assert!(records.windows(2).all(|r| r[0].millis <= r[1].millis));
+
+        // Invalid records tell where they are
+        std::fs::write(&path, "{\"millis\": 0}\n\n{\"millis\": \"soon\"}\n")?;
+        let error = load_records(&path).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::InvalidData);
+        assert!(error.to_string().contains("session.jsonl:3"));
+
+        // Cleaning
+        std::fs::remove_dir_all(dir)?;
+        Ok(())
+    }
+}
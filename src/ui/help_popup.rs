@@ -0,0 +1,98 @@
+use super::secondary_screen::{render_scrollbar, scroll_bounds};
+use super::theme::Theme;
+use super::{Hit, Rendered};
+use crate::config::HELP_POPUP_TITLE;
+use crate::keymap::{bindings, Context};
+use crate::model::Model;
+use ratatui::widgets::block::*;
+use ratatui::{prelude::*, widgets::*};
+
+//  ---------------------------
+// |                           |
+// |     ---------------       |
+// |    |               |      |
+// |    |     THIS      |      |
+// |    |               |      |
+// |     ---------------       |
+// |                           |
+//  ---------------------------
+pub fn render_help_popup(model: &Model, theme: &Theme, f: &mut Frame, rendered: &mut Rendered) {
+    let area = centered_rect(f.size(), 70, 70);
+    let context = Context::screen(model);
+
+    let outer_block = Block::new()
+        .borders(Borders::ALL)
+        .border_style(theme.border_style(true))
+        .title_alignment(Alignment::Center)
+        .padding(Padding {
+            left: 2,
+            right: 2,
+            top: 1,
+            bottom: 1,
+        })
+        .title(format!(" {} · {} ", HELP_POPUP_TITLE, context.title()));
+    let outer_area = area;
+    let inner_area = outer_block.inner(outer_area);
+
+    // One line per action of the screen below, then the keys of the popup itself
+    let width = bindings(context)
+        .chain(bindings(Context::Help))
+        .map(|binding| binding.label.chars().count())
+        .max()
+        .unwrap_or(0);
+    let mut lines = help_lines(context, width, theme);
+    lines.push(Line::default());
+    lines.extend(help_lines(Context::Help, width, theme));
+
+    let paragraph = Paragraph::new(lines)
+        .style(Style::default().fg(theme.text))
+        .wrap(Wrap { trim: false });
+    let scroll = scroll_bounds(&paragraph, inner_area, model.help_scroll);
+    let paragraph = paragraph.scroll((scroll.offset, 0));
+
+    // Render over the current screen
+    f.render_widget(Clear, outer_area);
+    f.render_widget(outer_block, outer_area);
+    f.render_widget(paragraph, inner_area);
+    render_scrollbar(f, outer_area, scroll);
+
+    // The popup hides everything below it
+    rendered.help_scroll = Some(scroll);
+    rendered.hits = vec![(outer_area, Hit::Help)];
+}
+
+/// Keys and description of every action of a context, keys right-aligned to `width`
+fn help_lines(context: Context, width: usize, theme: &Theme) -> Vec<Line<'static>> {
+    bindings(context)
+        .map(|binding| {
+            Line::from(vec![
+                Span::from(format!("{:>width$}", binding.label, width = width))
+                    .bold()
+                    .fg(theme.accent),
+                Span::from("  "),
+                Span::from(binding.description),
+            ])
+        })
+        .collect()
+}
+
+/// Rectangle of the given percentages of `area`, centered in it
+fn centered_rect(area: Rect, percent_x: u16, percent_y: u16) -> Rect {
+    let [_, area, _] = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Percentage((100 - percent_y) / 2),
+            Constraint::Percentage(percent_y),
+            Constraint::Percentage((100 - percent_y) / 2),
+        ])
+        .areas(area);
+    let [_, area, _] = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([
+            Constraint::Percentage((100 - percent_x) / 2),
+            Constraint::Percentage(percent_x),
+            Constraint::Percentage((100 - percent_x) / 2),
+        ])
+        .areas(area);
+    area
+}
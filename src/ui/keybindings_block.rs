@@ -1,7 +1,11 @@
-use crate::config::{KEYBINDINGS_INFO_MAIN_SCREEN, KEYBINDINGS_INFO_SECONDARY_SCREEN};
+use super::is_compact;
+use super::theme::Theme;
+use crate::keymap::{bindings, Binding, Context};
+use crate::message::Message;
 use crate::model::Model;
 use ratatui::widgets::block::*;
 use ratatui::{prelude::*, widgets::*};
+use unicode_width::UnicodeWidthStr;
 
 //  ---------------------------
 // |                           |
@@ -12,14 +16,21 @@ use ratatui::{prelude::*, widgets::*};
 //  ---------------------------
 // |           THIS            |
 //  ---------------------------
-fn render_keybindings(_: &Model, f: &mut Frame, area: Rect, keybindings_info: &str) {
-    // Make instruction block
-    let instruction_block = Block::default()
-        .borders(Borders::ALL)
-        .style(Style::default());
-
-    // Fill text
-    let instructions = Paragraph::new(Text::from(keybindings_info).bold().yellow())
+pub(crate) fn render_keybindings(model: &Model, theme: &Theme, f: &mut Frame, area: Rect) {
+    // Make instruction block, a single line in the compact layouts
+    let instruction_block = match is_compact(model, f.size()) {
+        true => Block::default(),
+        false => Block::default().borders(Borders::ALL),
+    }
+    .style(Style::default());
+    let width = instruction_block.inner(area).width as usize;
+
+    // Fill text. A status message replaces the keybindings until the next key press
+    let text = match &model.status {
+        Some(status) => Text::from(format!(" {} ", status)).fg(theme.info),
+        None => Text::from(keybindings_info(Context::screen(model), width)).fg(theme.accent),
+    };
+    let instructions = Paragraph::new(text.bold())
         .block(instruction_block)
         .centered();
 
@@ -27,10 +38,54 @@ fn render_keybindings(_: &Model, f: &mut Frame, area: Rect, keybindings_info: &s
     f.render_widget(instructions, area);
 }
 
-pub(crate) fn render_keybindings_main_screen(model: &Model, f: &mut Frame, area: Rect) {
-    render_keybindings(model, f, area, KEYBINDINGS_INFO_MAIN_SCREEN);
+/// Keys of the context and their hints, in the order of the help popup, as many as fit in
+/// `width` columns. The keys to go back, to open the help listing all of them and to quit
+/// always end it
+fn keybindings_info(context: Context, width: usize) -> String {
+    let always = |binding: &&Binding| {
+        let message = (binding.message)(binding.keys[0].code);
+        matches!(
+            message,
+            Some(Message::Back | Message::ToggleHelp | Message::Quit)
+        )
+    };
+    let hint = |binding: &Binding| format!("({}) {}", binding.label, binding.hint);
+
+    let last: Vec<String> = bindings(context).filter(always).map(hint).collect();
+    let len = |hints: &[String]| hints.join(" | ").width() + 2;
+    let mut hints: Vec<String> = vec![];
+    for binding in bindings(context).filter(|binding| !binding.hint.is_empty() && !always(binding))
+    {
+        hints.push(hint(binding));
+        if len(&[&hints[..], &last[..]].concat()) > width {
+            hints.pop();
+            break;
+        }
+    }
+    hints.extend(last);
+    format!(" {} ", hints.join(" | "))
 }
 
-pub(crate) fn render_keybindings_secondary_screen(model: &Model, f: &mut Frame, area: Rect) {
-    render_keybindings(model, f, area, KEYBINDINGS_INFO_SECONDARY_SCREEN);
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keybindings_fit_and_end_with_help() {
+        let info = keybindings_info(Context::Main, 60);
+        assert_eq!(
+            info,
+            " (Enter) Open | (a) Installed only | (?) Help | (q) Quit "
+        );
+        assert!(info.width() <= 60);
+
+        let info = keybindings_info(Context::Secondary, 1000);
+        assert!(info.contains("(D) Describe") && info.contains("(S) Secret"));
+        assert!(info.ends_with("(Esc) Back | (?) Help | (q) Quit "));
+        assert!(!info.contains("Down"));
+        assert_eq!(
+            keybindings_info(Context::Zoom, 10),
+            " (Esc) Back | (?) Help | (q) Quit "
+        );
+    }
 }
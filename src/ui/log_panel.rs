@@ -0,0 +1,69 @@
+use super::theme::Theme;
+use super::{screen_layout, Hit, Rendered};
+use crate::config::{LOG_PANEL_HEIGHT, LOG_PANEL_TITLE};
+use crate::logging::{log_dir, recent, LogLine};
+use crate::model::Model;
+use ratatui::widgets::block::*;
+use ratatui::{prelude::*, widgets::*};
+use tracing::Level;
+
+//  ---------------------------
+// |                           |
+//  ---------------------------
+// |                           |
+// |---------------------------|
+// |           THIS            |
+//  ---------------------------
+// |                           |
+//  ---------------------------
+pub fn render_log_panel(model: &Model, theme: &Theme, f: &mut Frame, rendered: &mut Rendered) {
+    // At the bottom of the panels, over them
+    let [_, data_area, _] = screen_layout(model, f.size());
+    let height = LOG_PANEL_HEIGHT.min(data_area.height);
+    let area = Rect {
+        y: data_area.bottom() - height,
+        height,
+        ..data_area
+    };
+
+    let title = match log_dir() {
+        Some(dir) => format!(" {} · {} ", LOG_PANEL_TITLE, dir.display()),
+        None => format!(" {} ", LOG_PANEL_TITLE),
+    };
+    let outer_block = Block::new()
+        .borders(Borders::ALL)
+        .border_style(theme.border_style(true))
+        .padding(Padding::horizontal(1))
+        .title(title);
+    let inner_area = outer_block.inner(area);
+
+    // The most recent messages, the last one at the bottom
+    let lines = recent();
+    let shown = lines.len().saturating_sub(inner_area.height as usize);
+    let lines: Vec<Line> = lines[shown..]
+        .iter()
+        .map(|line| log_line(line, theme))
+        .collect();
+
+    f.render_widget(Clear, area);
+    f.render_widget(outer_block, area);
+    f.render_widget(Paragraph::new(lines), inner_area);
+
+    // The panel hides what is below it
+    rendered.hits.push((area, Hit::Log));
+}
+
+/// Time, level and message of a log message, errors and warnings being highlighted
+fn log_line<'a>(line: &'a LogLine, theme: &Theme) -> Line<'a> {
+    let color = match line.level {
+        Level::ERROR => theme.error,
+        Level::WARN => theme.accent,
+        Level::INFO => theme.text,
+        _ => theme.comment,
+    };
+    Line::from(vec![
+        Span::from(format!("{:>8.3}s ", line.elapsed.as_secs_f64())).fg(theme.comment),
+        Span::from(format!("{:<5} ", line.level)).fg(color).bold(),
+        Span::from(line.message.as_str()).fg(color),
+    ])
+}
@@ -1,7 +1,11 @@
-use super::keybindings_block::render_keybindings_main_screen;
+use super::keybindings_block::render_keybindings;
+use super::secondary_screen::danger_badge;
+use super::shell::highlight_command;
+use super::theme::Theme;
 use super::title_block::render_title;
+use super::{list_hits, panel_areas, panel_padding, screen_layout, Hit, Rendered};
 use crate::config::*;
-use crate::model::Model;
+use crate::model::{position, Model, Panel};
 use ratatui::widgets::block::*;
 use ratatui::{prelude::*, widgets::*};
 
@@ -14,25 +18,28 @@ use ratatui::{prelude::*, widgets::*};
 //  ---------------------------
 // |         INSTRUCTIONS      |
 //  ---------------------------
-pub fn render_main_screen(model: &Model, f: &mut Frame) {
-    let [title_area, data_area, keybindings_area] = Layout::default()
-        .direction(Direction::Vertical)
-        .constraints([
-            Constraint::Length(3),
-            Constraint::Min(1),
-            Constraint::Length(3),
-        ])
-        .areas(f.size());
-
-    let [entries_area, preview_area] = Layout::default()
-        .direction(Direction::Horizontal)
-        .constraints(vec![Constraint::Percentage(25), Constraint::Percentage(75)])
-        .areas(data_area);
-
-    render_title(model, f, title_area);
-    render_entries(model, f, entries_area);
-    render_preview(model, f, preview_area);
-    render_keybindings_main_screen(model, f, keybindings_area);
+pub fn render_main_screen(model: &Model, theme: &Theme, f: &mut Frame) -> Rendered {
+    let [title_area, data_area, keybindings_area] = screen_layout(model, f.size());
+
+    // Narrow terminals stack the panels, or only show the focused one
+    let (entries_area, preview_area) = panel_areas(model, f.size(), data_area, 25);
+
+    render_title(model, theme, f, title_area);
+    let mut hits = vec![];
+    if let Some(entries_area) = entries_area {
+        hits = render_entries(model, theme, f, entries_area);
+    }
+    if let Some(preview_area) = preview_area {
+        render_preview(model, theme, f, preview_area);
+        hits.push((preview_area, Hit::Panel(Panel::Description)));
+    }
+    render_keybindings(model, theme, f, keybindings_area);
+
+    Rendered {
+        long_info_scroll: None,
+        help_scroll: None,
+        hits,
+    }
 }
 
 //  ---------------------------
@@ -44,19 +51,35 @@ pub fn render_main_screen(model: &Model, f: &mut Frame) {
 //  ---------------------------
 // |                           |
 //  ---------------------------
-fn render_entries(model: &Model, f: &mut Frame, area: Rect) {
+fn render_entries(model: &Model, theme: &Theme, f: &mut Frame, area: Rect) -> Vec<(Rect, Hit)> {
+    let mut hits = vec![(area, Hit::Panel(Panel::Entries))];
+
+    // Pinned entries are shown on top of the entry list
+    let pinned = model.pinned_entries();
+    let area = match pinned.is_empty() {
+        true => area,
+        false => {
+            let height = pinned.len().min(PINNED_ENTRIES_MAX) as u16 + 2;
+            let [pinned_area, area] = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Length(height), Constraint::Min(1)])
+                .areas(area);
+            hits.extend(render_pinned(model, theme, f, pinned_area, &pinned));
+            area
+        }
+    };
+
     let outer_block = Block::new()
         .borders(Borders::ALL)
         .title_alignment(Alignment::Left)
-        .padding(Padding {
-            left: 2,
-            right: 2,
-            top: 1,
-            bottom: 1,
-        })
+        .padding(panel_padding(model, f.size(), 2, 2))
         // .fg(TEXT_COLOR)
         // .bg(TODO_HEADER_BG)
-        .title(MAIN_SCREEN_ENTRIES_TITLE);
+        .title(format!(
+            "{} ({})",
+            MAIN_SCREEN_ENTRIES_TITLE, model.group_sort
+        ))
+        .border_style(theme.border_style(model.focus == Panel::Entries));
     let inner_block = Block::new()
         // .fg(TEXT_COLOR)
         // .bg(NORMAL_ROW_COLOR)
@@ -66,21 +89,37 @@ fn render_entries(model: &Model, f: &mut Frame, area: Rect) {
     let outer_area = area;
     let inner_area = outer_block.inner(outer_area);
 
-    // Get list of items
-    let items: Vec<String> = model
-        .entries
+    // Get list of items in the shown order, as a tree. Virtual groups are told apart
+    let order = model.visible_group_order();
+    let heights = vec![1; order.len()];
+    let items: Vec<ListItem> = order
         .iter()
-        .map(|entry_group| "· ".to_string() + &entry_group.description)
+        .map(|&idx| {
+            let entry_group = &model.entries[idx];
+            let indent = TREE_INDENT_STRING.repeat(entry_group.depth);
+            let symbol = match (model.has_children(idx), entry_group.collapsed) {
+                (true, true) => COLLAPSED_GROUP_SYMBOL_STRING,
+                (true, false) => EXPANDED_GROUP_SYMBOL_STRING,
+                (false, _) if entry_group.pinned => "",
+                (false, _) => "· ",
+            };
+            match entry_group.read_only {
+                false if entry_group.pinned => {
+                    ListItem::new(indent + PINNED_SYMBOL_STRING + symbol + &entry_group.description)
+                }
+                true => {
+                    ListItem::new(indent + PROJECT_GROUP_SYMBOL_STRING + &entry_group.description)
+                        .style(Style::default().add_modifier(Modifier::ITALIC))
+                }
+                false => ListItem::new(indent + symbol + &entry_group.description),
+            }
+        })
         .collect();
 
     let items = List::new(items)
         .block(inner_block)
-        .style(Style::default().fg(Color::White))
-        .highlight_style(
-            Style::default()
-                .add_modifier(Modifier::BOLD)
-                .bg(SELECTION_COLOR),
-        )
+        .style(Style::default().fg(theme.text))
+        .highlight_style(theme.selection_style())
         .highlight_symbol(ITEM_SELECTION_SYMBOL_STRING)
         // .scroll_padding(1)
         .repeat_highlight_symbol(true)
@@ -88,11 +127,70 @@ fn render_entries(model: &Model, f: &mut Frame, area: Rect) {
 
     // Get current selected item
     let mut current_state = ListState::default();
-    current_state.select(Some(model.idx_entrygroup));
+    current_state.select(Some(position(&order, model.idx_entrygroup)));
 
     // Render
     f.render_widget(outer_block, outer_area);
     f.render_stateful_widget(items, inner_area, &mut current_state);
+
+    // Clickable rows
+    hits.extend(list_hits(
+        inner_area,
+        current_state.offset(),
+        &heights,
+        |pos| Hit::Group(order[pos]),
+    ));
+    hits
+}
+
+//  ---------------------------
+// |                           |
+//  ---------------------------
+// |  THIS   |                 |
+// |---------|                 |
+// |         |                 |
+//  ---------------------------
+// |                           |
+//  ---------------------------
+fn render_pinned(
+    model: &Model,
+    theme: &Theme,
+    f: &mut Frame,
+    area: Rect,
+    pinned: &[(usize, usize)],
+) -> Vec<(Rect, Hit)> {
+    let outer_block = Block::new()
+        .borders(Borders::ALL)
+        .title_alignment(Alignment::Left)
+        .padding(Padding::horizontal(1))
+        .title(MAIN_SCREEN_PINNED_TITLE);
+    let outer_area = area;
+    let inner_area = outer_block.inner(outer_area);
+
+    // Number the pinned commands so they can be copied with a single key
+    let items: Vec<Line> = pinned
+        .iter()
+        .take(PINNED_ENTRIES_MAX)
+        .enumerate()
+        .map(|(i, &(idx_entrygroup, idx_entry))| {
+            let entry = &model.entries[idx_entrygroup].entries[idx_entry];
+            let mut spans = vec![Span::from(format!("{} ", i + 1)).bold().fg(theme.selection)];
+            if model.danger_of(&entry.command).is_some() {
+                spans.push(danger_badge(theme));
+            }
+            spans.push(Span::from(model.shown(entry, &entry.command).into_owned()));
+            Line::from(spans)
+        })
+        .collect();
+
+    let heights = vec![1; items.len()];
+    let items = List::new(items).style(Style::default().fg(theme.text));
+
+    f.render_widget(items, inner_area);
+    f.render_widget(outer_block, outer_area);
+
+    // Clickable rows
+    list_hits(inner_area, 0, &heights, |pos| Hit::Pinned(pos + 1))
 }
 
 //  ---------------------------
@@ -104,19 +202,15 @@ fn render_entries(model: &Model, f: &mut Frame, area: Rect) {
 //  ---------------------------
 // |                           |
 //  ---------------------------
-fn render_preview(model: &Model, f: &mut Frame, area: Rect) {
+fn render_preview(model: &Model, theme: &Theme, f: &mut Frame, area: Rect) {
     let outer_block = Block::new()
         .borders(Borders::ALL)
         .title_alignment(Alignment::Left)
-        .padding(Padding {
-            left: 2,
-            right: 1,
-            top: 1,
-            bottom: 1,
-        })
+        .padding(panel_padding(model, f.size(), 2, 1))
         // .fg(TEXT_COLOR)
         // .bg(TODO_HEADER_BG)
-        .title(MAIN_SCREEN_PREVIEW_TITLE);
+        .title(MAIN_SCREEN_PREVIEW_TITLE)
+        .border_style(theme.border_style(model.focus == Panel::Description));
     let inner_block = Block::new()
         // .fg(TEXT_COLOR)
         // .bg(NORMAL_ROW_COLOR)
@@ -124,16 +218,26 @@ fn render_preview(model: &Model, f: &mut Frame, area: Rect) {
     let outer_area = area;
     let inner_area = outer_block.inner(outer_area);
 
-    // Get list of items
-    let items: Vec<String> = model.entries[model.idx_entrygroup]
-        .entries
+    // Get list of items: the description and the highlighted command of each entry
+    let entries = &model.entries[model.idx_entrygroup].entries;
+    let items: Vec<Text> = model
+        .entry_order()
         .iter()
-        .map(|entry| entry.short_info.clone())
+        .map(|&idx| {
+            let entry = &entries[idx];
+            let mut command = highlight_command(&model.shown(entry, &entry.command), theme);
+            if model.danger_of(&entry.command).is_some() {
+                command.spans.insert(0, danger_badge(theme));
+            }
+            command.spans.insert(0, Span::raw("  "));
+            let short_info = model.shown(entry, &entry.short_info).into_owned();
+            Text::from(vec![Line::from(short_info), command])
+        })
         .collect();
 
     let items = List::new(items)
         .block(inner_block)
-        .style(Style::default().fg(Color::Yellow))
+        .style(Style::default().fg(theme.info))
         .highlight_style(Style::default().add_modifier(Modifier::BOLD))
         .highlight_symbol(">> ")
         .highlight_spacing(HighlightSpacing::Always)
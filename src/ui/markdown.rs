@@ -0,0 +1,286 @@
+use super::theme::Theme;
+use pulldown_cmark::{CodeBlockKind, Event, HeadingLevel, Options, Parser, Tag, TagEnd};
+use ratatui::prelude::*;
+
+/// Convert a Markdown string into styled text. Code blocks keep their whitespace
+pub fn markdown_to_text(markdown: &str, theme: &Theme) -> Text<'static> {
+    let mut writer = TextWriter {
+        code_style: Style::new().fg(theme.code),
+        heading_style: Style::new().fg(theme.heading).add_modifier(Modifier::BOLD),
+        link_style: Style::new()
+            .fg(theme.link)
+            .add_modifier(Modifier::UNDERLINED),
+        ..Default::default()
+    };
+
+    for event in Parser::new_ext(markdown, Options::ENABLE_STRIKETHROUGH) {
+        writer.handle(event);
+    }
+
+    writer.flush_line();
+    Text::from(writer.lines)
+}
+
+/// Accumulates the parser events into lines of styled spans
+#[derive(Default)]
+struct TextWriter {
+    lines: Vec<Line<'static>>,
+    /// Spans of the line being written
+    spans: Vec<Span<'static>>,
+    /// Nested inline styles (emphasis, strong, links...)
+    styles: Vec<Style>,
+    /// Nested lists, with the next number of the ordered ones
+    lists: Vec<Option<u64>>,
+    /// Whether we are inside a fenced or indented code block
+    in_code_block: bool,
+    /// Destinations of the links being written
+    links: Vec<String>,
+    /// Styles of the theme
+    code_style: Style,
+    heading_style: Style,
+    link_style: Style,
+}
+
+impl TextWriter {
+    fn style(&self) -> Style {
+        self.styles
+            .iter()
+            .fold(Style::default(), |acc, style| acc.patch(*style))
+    }
+
+    fn push(&mut self, text: String) {
+        let style = self.style();
+        self.spans.push(Span::styled(text, style));
+    }
+
+    fn flush_line(&mut self) {
+        if !self.spans.is_empty() {
+            self.lines.push(Line::from(std::mem::take(&mut self.spans)));
+        }
+    }
+
+    /// Separate blocks (paragraphs, headings, lists...) with an empty line
+    fn block_separator(&mut self) {
+        self.flush_line();
+        if self.lists.is_empty() && self.lines.last().is_some_and(|l| !l.spans.is_empty()) {
+            self.lines.push(Line::default());
+        }
+    }
+
+    fn handle(&mut self, event: Event) {
+        match event {
+            Event::Start(tag) => self.start(tag),
+            Event::End(tag) => self.end(tag),
+            Event::Text(text) if self.in_code_block => {
+                // Keep every line of the block as is
+                for line in text.lines() {
+                    self.spans
+                        .push(Span::styled(format!("  {}", line), self.code_style));
+                    self.flush_line();
+                }
+            }
+            Event::Text(text) => self.push(text.to_string()),
+            Event::Code(code) => {
+                let style = self.style().patch(self.code_style);
+                self.spans.push(Span::styled(format!("`{}`", code), style));
+            }
+            Event::SoftBreak => self.push(String::from(" ")),
+            Event::HardBreak => self.flush_line(),
+            Event::Rule => {
+                self.block_separator();
+                self.lines.push(Line::from("────────"));
+            }
+            Event::Html(html) | Event::InlineHtml(html) => self.push(html.to_string()),
+            _ => (),
+        }
+    }
+
+    fn start(&mut self, tag: Tag) {
+        match tag {
+            // Paragraphs inside list items stay next to the bullet
+            Tag::Paragraph if self.spans.is_empty() => self.block_separator(),
+            Tag::Heading { level, .. } => {
+                self.block_separator();
+                let prefix = match level {
+                    HeadingLevel::H1 => "",
+                    HeadingLevel::H2 => "▌ ",
+                    _ => "▏ ",
+                };
+                self.styles.push(self.heading_style);
+                self.push(prefix.to_string());
+            }
+            Tag::BlockQuote(_) => {
+                self.block_separator();
+                self.styles
+                    .push(Style::new().add_modifier(Modifier::ITALIC));
+                self.push(String::from("│ "));
+            }
+            Tag::CodeBlock(kind) => {
+                self.block_separator();
+                self.in_code_block = true;
+                if let CodeBlockKind::Fenced(lang) = kind {
+                    if !lang.is_empty() {
+                        self.lines
+                            .push(Line::styled(format!("  {}", lang), Modifier::DIM));
+                    }
+                }
+            }
+            Tag::List(start) => {
+                if self.lists.is_empty() {
+                    self.block_separator();
+                }
+                self.flush_line();
+                self.lists.push(start);
+            }
+            Tag::Item => {
+                self.flush_line();
+                let indent = "  ".repeat(self.lists.len().saturating_sub(1));
+                let bullet = match self.lists.last_mut() {
+                    Some(Some(n)) => {
+                        *n += 1;
+                        format!("{}{}. ", indent, *n - 1)
+                    }
+                    _ => format!("{}• ", indent),
+                };
+                self.spans.push(Span::raw(bullet));
+            }
+            Tag::Emphasis => self
+                .styles
+                .push(Style::new().add_modifier(Modifier::ITALIC)),
+            Tag::Strong => self.styles.push(Style::new().add_modifier(Modifier::BOLD)),
+            Tag::Strikethrough => self
+                .styles
+                .push(Style::new().add_modifier(Modifier::CROSSED_OUT)),
+            Tag::Link { dest_url, .. } => {
+                self.styles.push(self.link_style);
+                self.links.push(dest_url.to_string());
+            }
+            _ => (),
+        }
+    }
+
+    fn end(&mut self, tag: TagEnd) {
+        match tag {
+            TagEnd::Paragraph if self.lists.is_empty() => self.flush_line(),
+            TagEnd::Heading(_) | TagEnd::BlockQuote(_) => {
+                self.styles.pop();
+                self.flush_line();
+            }
+            TagEnd::CodeBlock => {
+                self.in_code_block = false;
+                self.flush_line();
+            }
+            TagEnd::List(_) => {
+                self.flush_line();
+                self.lists.pop();
+            }
+            TagEnd::Item => self.flush_line(),
+            TagEnd::Emphasis | TagEnd::Strong | TagEnd::Strikethrough => {
+                self.styles.pop();
+            }
+            TagEnd::Link => {
+                self.styles.pop();
+                // Show the destination, unless it is the text itself (autolinks)
+                if let Some(url) = self.links.pop() {
+                    let text = self.spans.last().map(|s| s.content.to_string());
+                    if text.as_deref() != Some(url.as_str()) {
+                        self.spans
+                            .push(Span::styled(format!(" ({})", url), Modifier::DIM));
+                    }
+                }
+            }
+            _ => (),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn to_strings(text: &Text) -> Vec<String> {
+        text.lines
+            .iter()
+            .map(|line| line.spans.iter().map(|s| s.content.as_ref()).collect())
+            .collect()
+    }
+
+    #[test]
+    fn render_paragraphs_and_inline_styles() {
+        let text = markdown_to_text(
+            "Some **bold** and *italic*\ntext\n\nwith `code`",
+            &Theme::dark(),
+        );
+
+        assert_eq!(
+            to_strings(&text),
+            vec!["Some bold and italic text", "", "with `code`"]
+        );
+        let bold = &text.lines[0].spans[1];
+        assert_eq!(bold.content, "bold");
+        assert!(bold.style.add_modifier.contains(Modifier::BOLD));
+        let italic = &text.lines[0].spans[3];
+        assert!(italic.style.add_modifier.contains(Modifier::ITALIC));
+        assert_eq!(text.lines[2].spans[1].style.fg, Some(Theme::dark().code));
+    }
+
+    #[test]
+    fn render_headings_and_lists() {
+        let text = markdown_to_text(
+            "# Title\n\n- one\n- two\n  1. nested\n\n2. first\n3. second",
+            &Theme::dark(),
+        );
+
+        assert_eq!(
+            to_strings(&text),
+            vec![
+                "Title",
+                "",
+                "• one",
+                "• two",
+                "  1. nested",
+                "",
+                "2. first",
+                "3. second"
+            ]
+        );
+        assert_eq!(text.lines[0].spans[1].style.fg, Some(Theme::dark().heading));
+    }
+
+    #[test]
+    fn render_code_blocks_preserving_whitespace() {
+        let text = markdown_to_text(
+            "Example:\n\n```bash\nfor f in *; do\n    echo $f\ndone\n```",
+            &Theme::dark(),
+        );
+
+        assert_eq!(
+            to_strings(&text),
+            vec![
+                "Example:",
+                "",
+                "  bash",
+                "  for f in *; do",
+                "      echo $f",
+                "  done"
+            ]
+        );
+    }
+
+    #[test]
+    fn render_links() {
+        let text = markdown_to_text(
+            "See [the docs](https://example.com) or <https://a.b>",
+            &Theme::dark(),
+        );
+
+        assert_eq!(
+            to_strings(&text),
+            vec!["See the docs (https://example.com) or https://a.b"]
+        );
+        assert!(text.lines[0].spans[1]
+            .style
+            .add_modifier
+            .contains(Modifier::UNDERLINED));
+    }
+}
@@ -1,5 +1,226 @@
+pub mod help_popup;
 pub mod keybindings_block;
+pub mod log_panel;
 pub mod main_screen;
+pub mod markdown;
+pub mod prompt_popup;
 pub mod secondary_screen;
+pub mod shell;
+pub mod theme;
 pub mod title_block;
 pub mod tui;
+pub mod zoom_screen;
+
+use crate::model::{Model, Panel, Scroll};
+use ratatui::layout::{Constraint, Direction, Layout, Position, Rect};
+use ratatui::widgets::Padding;
+use serde::{Deserialize, Serialize};
+
+/// Something that can be clicked on the screen
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Hit {
+    /// Anywhere in a panel
+    Panel(Panel),
+    /// Entry group, by index in the model
+    Group(usize),
+    /// Entry of the selected group, by index in the model
+    Entry(usize),
+    /// Nth pinned entry, starting at 1
+    Pinned(usize),
+    /// Help popup
+    Help,
+    /// Log panel
+    Log,
+}
+
+/// Information only known once a frame has been rendered, fed back into the model
+#[derive(Debug, Default)]
+pub struct Rendered {
+    /// Bounds of the scrollable long description, if shown
+    pub long_info_scroll: Option<Scroll>,
+    /// Bounds of the scrollable help popup, if shown
+    pub help_scroll: Option<Scroll>,
+    /// Clickable areas, from the outermost to the innermost
+    pub hits: Vec<(Rect, Hit)>,
+}
+
+impl Rendered {
+    /// Innermost clickable thing at the given terminal cell
+    pub fn hit_at(&self, column: u16, row: u16) -> Option<Hit> {
+        self.hits
+            .iter()
+            .rev()
+            .find(|(area, _)| area.contains(Position { x: column, y: row }))
+            .map(|(_, hit)| *hit)
+    }
+}
+
+/// Areas of the visible rows of a list rendered in `area`, scrolled to `offset`, whose
+/// items take `heights` lines. `hit` gives what the item at some position is
+pub(crate) fn list_hits(
+    area: Rect,
+    offset: usize,
+    heights: &[usize],
+    hit: impl Fn(usize) -> Hit,
+) -> Vec<(Rect, Hit)> {
+    let mut hits = vec![];
+    let mut y = area.y;
+
+    for (pos, &height) in heights.iter().enumerate().skip(offset) {
+        if y >= area.bottom() {
+            break;
+        }
+        let height = (height as u16).min(area.bottom() - y);
+        hits.push((Rect::new(area.x, y, area.width, height), hit(pos)));
+        y += height;
+    }
+
+    hits
+}
+
+/// Whether the compact layouts are used: in inline mode, or when the terminal is short
+pub(crate) fn is_compact(model: &Model, screen: Rect) -> bool {
+    model.compact || screen.height < model.config.breakpoints.compact_height
+}
+
+/// Split the screen into the title bar, the panels and the keybindings bar. The compact
+/// layouts have no title bar and a single line of keybindings
+pub(crate) fn screen_layout(model: &Model, area: Rect) -> [Rect; 3] {
+    let (title_height, keybindings_height) = match is_compact(model, area) {
+        true => (0, 1),
+        false => (3, 3),
+    };
+    Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(title_height),
+            Constraint::Min(1),
+            Constraint::Length(keybindings_height),
+        ])
+        .areas(area)
+}
+
+/// How the list and the description panels of a screen are arranged
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Arrangement {
+    SideBySide,
+    Stacked,
+    /// Only the focused panel
+    Single,
+}
+
+impl Arrangement {
+    pub(crate) fn of(model: &Model, screen: Rect) -> Arrangement {
+        let breakpoints = model.config.breakpoints;
+        if screen.width < breakpoints.single_panel_width {
+            Arrangement::Single
+        } else if screen.width < breakpoints.stacked_width {
+            Arrangement::Stacked
+        } else {
+            Arrangement::SideBySide
+        }
+    }
+}
+
+/// Areas of the list panel and of the description panel, taking `list_percentage` of the
+/// width when side by side. The panel without focus is not shown in small terminals
+pub(crate) fn panel_areas(
+    model: &Model,
+    screen: Rect,
+    area: Rect,
+    list_percentage: u16,
+) -> (Option<Rect>, Option<Rect>) {
+    let split = |direction: Direction, percentage: u16| -> (Option<Rect>, Option<Rect>) {
+        let [list_area, description_area] = Layout::default()
+            .direction(direction)
+            .constraints([
+                Constraint::Percentage(percentage),
+                Constraint::Percentage(100 - percentage),
+            ])
+            .areas(area);
+        (Some(list_area), Some(description_area))
+    };
+
+    match Arrangement::of(model, screen) {
+        Arrangement::SideBySide => split(Direction::Horizontal, list_percentage),
+        Arrangement::Stacked => split(Direction::Vertical, 50),
+        Arrangement::Single => match model.focus {
+            Panel::Entries => (Some(area), None),
+            Panel::Description => (None, Some(area)),
+        },
+    }
+}
+
+/// Padding of the panels, without blank lines in the compact layouts
+pub(crate) fn panel_padding(model: &Model, screen: Rect, left: u16, right: u16) -> Padding {
+    match is_compact(model, screen) {
+        true => Padding::horizontal(1),
+        false => Padding {
+            left,
+            right,
+            top: 1,
+            bottom: 1,
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hit_test_list_rows() {
+        let area = Rect::new(2, 3, 10, 4);
+        let hits = list_hits(area, 1, &[1, 2, 1, 3], Hit::Entry);
+
+        assert_eq!(
+            hits,
+            vec![
+                (Rect::new(2, 3, 10, 2), Hit::Entry(1)),
+                (Rect::new(2, 5, 10, 1), Hit::Entry(2)),
+                (Rect::new(2, 6, 10, 1), Hit::Entry(3)),
+            ]
+        );
+
+        let rendered = Rendered {
+            long_info_scroll: None,
+            help_scroll: None,
+            hits: [vec![(area, Hit::Panel(Panel::Entries))], hits].concat(),
+        };
+        assert_eq!(rendered.hit_at(2, 4), Some(Hit::Entry(1)));
+        assert_eq!(rendered.hit_at(11, 6), Some(Hit::Entry(3)));
+        assert_eq!(rendered.hit_at(12, 6), None);
+    }
+
+    #[test]
+    fn arrange_panels_by_terminal_size() {
+        let mut model = Model::default();
+        let area = Rect::new(0, 0, 100, 20);
+        let split =
+            |model: &Model, width: u16| panel_areas(model, Rect::new(0, 0, width, 30), area, 25);
+
+        assert_eq!(
+            split(&model, 120),
+            (
+                Some(Rect::new(0, 0, 25, 20)),
+                Some(Rect::new(25, 0, 75, 20))
+            )
+        );
+        assert_eq!(
+            split(&model, 80),
+            (
+                Some(Rect::new(0, 0, 100, 10)),
+                Some(Rect::new(0, 10, 100, 10))
+            )
+        );
+        assert_eq!(split(&model, 40), (Some(area), None));
+        model.focus = Panel::Description;
+        assert_eq!(split(&model, 40), (None, Some(area)));
+
+        // Short terminals collapse the title and keybindings bars
+        assert!(!is_compact(&model, Rect::new(0, 0, 120, 30)));
+        assert!(is_compact(&model, Rect::new(0, 0, 120, 20)));
+        model.config.breakpoints.compact_height = 10;
+        assert!(!is_compact(&model, Rect::new(0, 0, 120, 20)));
+    }
+}
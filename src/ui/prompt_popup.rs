@@ -0,0 +1,89 @@
+use super::secondary_screen::long_info_paragraph;
+use super::theme::Theme;
+use super::Rendered;
+use crate::config::PROMPT_POPUP_WIDTH;
+use crate::model::prompt::{Prompt, PromptKind};
+use crate::model::Model;
+use ratatui::widgets::block::*;
+use ratatui::{prelude::*, widgets::*};
+
+/// Shown in place of each typed character of the passphrases and secret values
+const MASK_CHAR: char = '•';
+
+//  ---------------------------
+// |                           |
+// |     ---------------       |
+// |    |     THIS      |      |
+// |     ---------------       |
+// |                           |
+//  ---------------------------
+pub fn render_prompt_popup(
+    model: &Model,
+    prompt: &Prompt,
+    theme: &Theme,
+    f: &mut Frame,
+    rendered: &mut Rendered,
+) {
+    let screen = f.size();
+    // A generated description is shown whole, or as much of it as fits
+    let proposal = match &prompt.kind {
+        PromptKind::LongInfo(long_info) => {
+            Some(long_info_paragraph(model, theme, long_info.clone()))
+        }
+        _ => None,
+    };
+    let width = match proposal {
+        Some(_) => (screen.width * 4 / 5).max(PROMPT_POPUP_WIDTH),
+        None => PROMPT_POPUP_WIDTH,
+    }
+    .min(screen.width);
+    let height = match &proposal {
+        // Borders and horizontal padding included
+        Some(paragraph) => paragraph.line_count(width.saturating_sub(4)) as u16 + 2,
+        None => 3,
+    }
+    .min(screen.height);
+    let area = Rect {
+        x: screen.x + (screen.width - width) / 2,
+        y: screen.y + (screen.height - height) / 2,
+        width,
+        height,
+    };
+
+    // The status message, e.g. a wrong passphrase, goes below the typed text, as there may
+    // be nothing else on the screen
+    let mut outer_block = Block::new()
+        .borders(Borders::ALL)
+        .border_style(theme.border_style(true))
+        .padding(Padding::horizontal(1))
+        .title(format!(" {} ", prompt.title()));
+    if let Some(status) = &model.status {
+        outer_block = outer_block.title(
+            Title::from(format!(" {} ", status).fg(theme.error))
+                .position(Position::Bottom)
+                .alignment(Alignment::Right),
+        );
+    }
+    let inner_area = outer_block.inner(area);
+
+    f.render_widget(Clear, area);
+    f.render_widget(&outer_block, area);
+    // Nothing can be clicked until the prompt is closed
+    rendered.hits.clear();
+    if let Some(paragraph) = proposal {
+        f.render_widget(paragraph, inner_area);
+        return;
+    }
+
+    // The end of the text is shown, followed by the cursor
+    let text: String = match prompt.masked() {
+        true => prompt.input.chars().map(|_| MASK_CHAR).collect(),
+        false => prompt.input.clone(),
+    };
+    let len = text.chars().count();
+    let shown = len.min(inner_area.width.saturating_sub(1) as usize);
+    let text: String = text.chars().skip(len - shown).collect();
+
+    f.render_widget(Paragraph::new(text).fg(theme.text), inner_area);
+    f.set_cursor(inner_area.x + shown as u16, inner_area.y);
+}
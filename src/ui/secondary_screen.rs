@@ -1,9 +1,14 @@
-use super::keybindings_block::render_keybindings_secondary_screen;
+use super::keybindings_block::render_keybindings;
+use super::markdown::markdown_to_text;
+use super::shell::highlight_command;
+use super::theme::Theme;
 use super::title_block::render_title;
+use super::{is_compact, list_hits, panel_areas, panel_padding, screen_layout, Hit, Rendered};
 use crate::config::*;
-use crate::model::Model;
+use crate::model::{position, Model, Panel, Scroll};
 use ratatui::widgets::block::*;
 use ratatui::{prelude::*, widgets::*};
+use unicode_width::UnicodeWidthChar;
 
 //  ----------------------------
 // |           TITLE            |
@@ -14,25 +19,29 @@ use ratatui::{prelude::*, widgets::*};
 //  ----------------------------
 // |         INSTRUCTIONS       |
 //  ----------------------------
-pub fn render_secondary_screen(model: &Model, f: &mut Frame) {
-    let [title_area, data_area, instruction_area] = Layout::default()
-        .direction(Direction::Vertical)
-        .constraints([
-            Constraint::Length(3),
-            Constraint::Min(1),
-            Constraint::Length(3),
-        ])
-        .areas(f.size());
-
-    let [entries_area, long_info_area] = Layout::default()
-        .direction(Direction::Horizontal)
-        .constraints(vec![Constraint::Percentage(70), Constraint::Percentage(30)])
-        .areas(data_area);
-
-    render_title(model, f, title_area);
-    render_entries(model, f, entries_area);
-    render_long_info(model, f, long_info_area);
-    render_keybindings_secondary_screen(model, f, instruction_area);
+pub fn render_secondary_screen(model: &Model, theme: &Theme, f: &mut Frame) -> Rendered {
+    let [title_area, data_area, instruction_area] = screen_layout(model, f.size());
+
+    // Narrow terminals stack the panels, or only show the focused one
+    let (entries_area, long_info_area) = panel_areas(model, f.size(), data_area, 70);
+
+    render_title(model, theme, f, title_area);
+    let mut hits = vec![];
+    if let Some(entries_area) = entries_area {
+        hits = render_entries(model, theme, f, entries_area);
+    }
+    let mut long_info_scroll = None;
+    if let Some(long_info_area) = long_info_area {
+        long_info_scroll = Some(render_long_info(model, theme, f, long_info_area));
+        hits.push((long_info_area, Hit::Panel(Panel::Description)));
+    }
+    render_keybindings(model, theme, f, instruction_area);
+
+    Rendered {
+        long_info_scroll,
+        help_scroll: None,
+        hits,
+    }
 }
 
 //  ----------------------------
@@ -44,19 +53,15 @@ pub fn render_secondary_screen(model: &Model, f: &mut Frame) {
 //  ----------------------------
 // |                            |
 //  ----------------------------
-fn render_entries(model: &Model, f: &mut Frame, area: Rect) {
+fn render_entries(model: &Model, theme: &Theme, f: &mut Frame, area: Rect) -> Vec<(Rect, Hit)> {
     let outer_block = Block::new()
         .borders(Borders::ALL)
         .title_alignment(Alignment::Left)
-        .padding(Padding {
-            left: 2,
-            right: 2,
-            top: 1,
-            bottom: 1,
-        })
+        .padding(panel_padding(model, f.size(), 2, 2))
         // .fg(TEXT_COLOR)
         // .bg(TODO_HEADER_BG)
-        .title(SECONDARY_SCREEN_ENTRIES_TITLE);
+        .title(entries_title(model, f.size()))
+        .border_style(theme.border_style(model.focus == Panel::Entries));
     let inner_block = Block::new()
         // .fg(TEXT_COLOR)
         // .bg(NORMAL_ROW_COLOR)
@@ -66,26 +71,46 @@ fn render_entries(model: &Model, f: &mut Frame, area: Rect) {
     let outer_area = area;
     let inner_area = outer_block.inner(outer_area);
 
-    // Get list of items
-    let (cmds, _infos): (Vec<String>, Vec<String>) = model.entries[model.idx_entrygroup]
-        .entries
+    // Get list of items in the shown order. Long commands are wrapped or truncated
+    let width = inner_area
+        .width
+        .saturating_sub(ITEM_SELECTION_SYMBOL_STRING.chars().count() as u16)
+        as usize;
+    let entries = &model.entries[model.idx_entrygroup].entries;
+    let order = model.entry_order();
+    let (cmds, _infos): (Vec<Text>, Vec<String>) = order
         .iter()
+        .map(|&idx| &entries[idx])
         .map(|entry| {
-            let cmd = entry.command.clone();
+            let mut cmd = highlight_command(&model.shown(entry, &entry.command), theme);
+            if entry.pinned {
+                cmd.spans.insert(0, Span::raw(PINNED_SYMBOL_STRING));
+            }
+            if model.danger_of(&entry.command).is_some() {
+                cmd.spans.insert(0, danger_badge(theme));
+            }
+            if model.is_missing(&entry.command) {
+                cmd.spans
+                    .insert(0, Span::from(MISSING_SYMBOL_STRING).fg(theme.comment));
+            }
+            // Marked entries show their position in the copied sequence
+            if let Some(n) = model.mark_of(&entry.command) {
+                cmd.spans
+                    .insert(0, Span::from(format!("[{}] ", n)).bold().fg(theme.accent));
+                cmd = cmd.underlined();
+            }
+            let cmd = fit_command(cmd, width, model.wrap_commands);
             let info = entry.short_info.clone();
             (cmd, info)
         })
         .unzip();
+    let heights: Vec<usize> = cmds.iter().map(|cmd| cmd.height()).collect();
 
     let cmds = List::new(cmds)
         .block(inner_block.clone())
-        .style(Style::default().fg(Color::White))
-        .highlight_style(
-            Style::default()
-                .add_modifier(Modifier::BOLD)
-                .bg(SELECTION_COLOR),
-        )
-        .highlight_symbol(">> ")
+        .style(Style::default().fg(theme.text))
+        .highlight_style(theme.selection_style())
+        .highlight_symbol(ITEM_SELECTION_SYMBOL_STRING)
         // .scroll_padding(1)
         .repeat_highlight_symbol(true)
         .direction(ListDirection::TopToBottom);
@@ -102,11 +127,43 @@ fn render_entries(model: &Model, f: &mut Frame, area: Rect) {
 
     // Get current selected item
     let mut current_state = ListState::default();
-    current_state.select(Some(model.idx_entry));
+    current_state.select(Some(position(&order, model.idx_entry)));
 
     // Render
     f.render_stateful_widget(cmds, inner_area, &mut current_state);
     f.render_widget(outer_block, outer_area);
+
+    // Clickable rows
+    let mut hits = vec![(outer_area, Hit::Panel(Panel::Entries))];
+    hits.extend(list_hits(
+        inner_area,
+        current_state.offset(),
+        &heights,
+        |pos| Hit::Entry(order[pos]),
+    ));
+    hits
+}
+
+/// Badge of the commands that need a confirmation to be copied
+pub fn danger_badge(theme: &Theme) -> Span<'static> {
+    Span::from(DANGER_SYMBOL_STRING).bold().fg(theme.error)
+}
+
+/// Title of the entry list: sort order and marked entries, if any. The compact layouts
+/// have no title bar, so the path of the group goes here
+fn entries_title(model: &Model, screen: Rect) -> String {
+    let title = match is_compact(model, screen) {
+        true => model.breadcrumb().join(BREADCRUMB_SEPARATOR_STRING),
+        false => SECONDARY_SCREEN_ENTRIES_TITLE.to_string(),
+    };
+    let title = match model.hide_missing {
+        true => format!("{} ({}, installed only)", title, model.entry_sort),
+        false => format!("{} ({})", title, model.entry_sort),
+    };
+    match model.marked.len() {
+        0 => title,
+        n => format!("{} · {} marked, joined with {}", title, n, model.separator),
+    }
 }
 
 //  ----------------------------
@@ -118,19 +175,15 @@ fn render_entries(model: &Model, f: &mut Frame, area: Rect) {
 //  ----------------------------
 // |                            |
 //  ----------------------------
-fn render_long_info(model: &Model, f: &mut Frame, area: Rect) {
+fn render_long_info(model: &Model, theme: &Theme, f: &mut Frame, area: Rect) -> Scroll {
     let outer_block = Block::new()
         .borders(Borders::ALL)
         .title_alignment(Alignment::Left)
-        .padding(Padding {
-            left: 2,
-            right: 1,
-            top: 1,
-            bottom: 1,
-        })
+        .padding(panel_padding(model, f.size(), 2, 1))
         // .fg(TEXT_COLOR)
         // .bg(TODO_HEADER_BG)
-        .title(SECONDARY_SCREEN_LONG_INFO_TITLE);
+        .title(SECONDARY_SCREEN_LONG_INFO_TITLE)
+        .border_style(theme.border_style(model.focus == Panel::Description));
     let inner_block = Block::new()
         // .fg(TEXT_COLOR)
         // .bg(NORMAL_ROW_COLOR)
@@ -138,18 +191,208 @@ fn render_long_info(model: &Model, f: &mut Frame, area: Rect) {
     let outer_area = area;
     let inner_area = outer_block.inner(outer_area);
 
-    // Get current long info
-    let long_info = model.entries[model.idx_entrygroup].entries[model.idx_entry]
-        .long_info
-        .clone();
+    // Get current long info, with its secrets masked
+    let entry = &model.entries[model.idx_entrygroup].entries[model.idx_entry];
+    let long_info = model.shown(entry, &entry.long_info).into_owned();
 
-    let paragraph = Paragraph::new(long_info)
-        .block(inner_block)
-        .style(Style::new().white().on_black())
-        .centered()
-        .wrap(Wrap { trim: true });
+    let paragraph = long_info_paragraph(model, theme, long_info).block(inner_block);
+
+    // Only scroll as far as the text goes
+    let scroll = scroll_bounds(&paragraph, inner_area, model.long_info_scroll);
+    let paragraph = paragraph.scroll((scroll.offset, 0));
 
     // Render
     f.render_widget(paragraph, inner_area);
     f.render_widget(outer_block, outer_area);
+    render_scrollbar(f, outer_area, scroll);
+
+    scroll
+}
+
+/// Build the paragraph of a long description.
+/// Markdown keeps the whitespace of code examples, plain text is centered
+pub(crate) fn long_info_paragraph(
+    model: &Model,
+    theme: &Theme,
+    long_info: String,
+) -> Paragraph<'static> {
+    match model.config.markdown {
+        true => Paragraph::new(markdown_to_text(&long_info, theme))
+            .left_aligned()
+            .wrap(Wrap { trim: false }),
+        false => Paragraph::new(long_info)
+            .centered()
+            .wrap(Wrap { trim: true }),
+    }
+    .style(theme.description_style())
+}
+
+/// Clamp the scroll state to the text of `paragraph` once rendered in `area`
+pub(crate) fn scroll_bounds(paragraph: &Paragraph, area: Rect, scroll: Scroll) -> Scroll {
+    let lines = paragraph.line_count(area.width) as u16;
+    let max = lines.saturating_sub(area.height);
+    Scroll {
+        offset: scroll.offset.min(max),
+        max,
+        page: area.height,
+    }
+}
+
+/// Render a vertical scrollbar on the right border of `area` when the text does not fit
+pub(crate) fn render_scrollbar(f: &mut Frame, area: Rect, scroll: Scroll) {
+    if scroll.max == 0 {
+        return;
+    }
+
+    let scrollbar = Scrollbar::new(ScrollbarOrientation::VerticalRight)
+        .begin_symbol(None)
+        .end_symbol(None);
+    let mut state = ScrollbarState::new(scroll.max as usize + 1).position(scroll.offset as usize);
+
+    f.render_stateful_widget(
+        scrollbar,
+        area.inner(&Margin {
+            vertical: 1,
+            horizontal: 0,
+        }),
+        &mut state,
+    );
+}
+
+/// Fit a highlighted command in `width` columns, either wrapping it in several lines or
+/// truncating it with an ellipsis
+fn fit_command(command: Line<'static>, width: usize, wrap: bool) -> Text<'static> {
+    if width == 0 || command.width() <= width {
+        return Text::from(command);
+    }
+
+    // Styled chars of the command
+    let chars: Vec<(char, Style)> = command
+        .spans
+        .iter()
+        .flat_map(|span| span.content.chars().map(move |c| (c, span.style)))
+        .collect();
+
+    // Rebuild a line from styled chars, joining the chars that share a style
+    let to_line = |chars: &[(char, Style)]| -> Line<'static> {
+        let mut spans: Vec<Span> = vec![];
+        for &(c, style) in chars {
+            match spans.last_mut() {
+                Some(span) if span.style == style => span.content.to_mut().push(c),
+                _ => spans.push(Span::styled(c.to_string(), style)),
+            }
+        }
+        Line::from(spans)
+    };
+
+    // Columns taken by each char, as wide glyphs (CJK, emoji) take two
+    let char_width = |c: char| c.width().unwrap_or(0);
+
+    match wrap {
+        true => {
+            let mut lines: Vec<Line> = vec![];
+            let (mut start, mut line_width) = (0, 0);
+            for (i, &(c, _)) in chars.iter().enumerate() {
+                if line_width + char_width(c) > width && i > start {
+                    lines.push(to_line(&chars[start..i]));
+                    (start, line_width) = (i, 0);
+                }
+                line_width += char_width(c);
+            }
+            lines.push(to_line(&chars[start..]));
+            Text::from(lines)
+        }
+        false => {
+            // Room is left for the ellipsis
+            let mut line_width = 0;
+            let len = chars
+                .iter()
+                .take_while(|(c, _)| {
+                    line_width += char_width(*c);
+                    line_width < width
+                })
+                .count();
+            let mut line = to_line(&chars[..len]);
+            line.spans.push(Span::raw("…"));
+            Text::from(line)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fit_long_commands() {
+        let to_strings = |text: &Text| -> Vec<String> {
+            text.lines
+                .iter()
+                .map(|line| line.spans.iter().map(|s| s.content.as_ref()).collect())
+                .collect()
+        };
+
+        let short = fit_command(highlight_command("ls -la", &Theme::dark()), 10, false);
+        assert_eq!(to_strings(&short), vec!["ls -la"]);
+
+        let truncated = fit_command(
+            highlight_command("g++ -o main main.cpp", &Theme::dark()),
+            10,
+            false,
+        );
+        assert_eq!(to_strings(&truncated), vec!["g++ -o ma…"]);
+
+        let wrapped = fit_command(
+            highlight_command("g++ -o main main.cpp", &Theme::dark()),
+            10,
+            true,
+        );
+        assert_eq!(to_strings(&wrapped), vec!["g++ -o mai", "n main.cpp"]);
+        // Wide chars take two columns
+        let wide = fit_command(Line::raw("echo 日本語のテキスト"), 10, false);
+        assert_eq!(to_strings(&wide), vec!["echo 日本…"]);
+        let wide = fit_command(Line::raw("echo 日本語のテキスト"), 10, true);
+        assert_eq!(to_strings(&wide), vec!["echo 日本", "語のテキス", "ト"]);
+
+        // Highlighting is kept across lines
+        assert_eq!(wrapped.lines[1].spans[0].content, "n");
+        assert_eq!(
+            wrapped.lines[1].spans[0].style,
+            wrapped.lines[0].spans[4].style
+        );
+    }
+
+    #[test]
+    fn clamp_scroll_to_text() {
+        let paragraph = Paragraph::new("1\n2\n3\n4\n5\n6");
+        let area = Rect::new(0, 0, 10, 4);
+
+        let scroll = scroll_bounds(
+            &paragraph,
+            area,
+            Scroll {
+                offset: 10,
+                ..Default::default()
+            },
+        );
+        assert_eq!(
+            scroll,
+            Scroll {
+                offset: 2,
+                max: 2,
+                page: 4
+            }
+        );
+
+        let area = Rect::new(0, 0, 10, 8);
+        let scroll = scroll_bounds(
+            &paragraph,
+            area,
+            Scroll {
+                offset: 1,
+                ..Default::default()
+            },
+        );
+        assert_eq!(scroll.offset, 0);
+    }
 }
@@ -0,0 +1,291 @@
+use super::theme::Theme;
+use crate::model::secrets::placeholder_at;
+use ratatui::prelude::*;
+
+/// Kind of a piece of a shell command
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenKind {
+    /// Name of the program run by a command, e.g. `git`
+    Program,
+    /// Option of the program, e.g. `--help`
+    Flag,
+    /// Any other word
+    Argument,
+    /// Single or double quoted string
+    String,
+    /// Variable expansion or assignment, e.g. `$HOME`, `${x}`, `$(pwd)`, `CC=gcc`
+    Variable,
+    /// Pipe, list operator or redirection, e.g. `|`, `&&`, `>`
+    Operator,
+    /// Value to fill in by the user, e.g. `<file>`
+    Placeholder,
+    Comment,
+    Whitespace,
+}
+
+/// Split a shell command into tokens. Concatenating the tokens gives back the command
+pub fn tokenize(command: &str) -> Vec<(TokenKind, &str)> {
+    let chars: Vec<(usize, char)> = command.char_indices().collect();
+    let byte_at = |i: usize| chars.get(i).map_or(command.len(), |&(b, _)| b);
+    let char_at = |i: usize| chars.get(i).map(|&(_, c)| c);
+
+    let mut tokens = vec![];
+    // Whether the next word is the program of a new command
+    let mut expect_program = true;
+    // Kind of the word being read, shared by all its pieces (e.g. `-I<path>`)
+    let mut word_kind: Option<TokenKind> = None;
+    let mut i = 0;
+
+    while let Some(c) = char_at(i) {
+        let start = i;
+        let kind = match c {
+            c if c.is_whitespace() => {
+                while char_at(i).is_some_and(|c| c.is_whitespace()) {
+                    i += 1;
+                }
+                word_kind = None;
+                TokenKind::Whitespace
+            }
+            '#' if word_kind.is_none() => {
+                i = chars.len();
+                TokenKind::Comment
+            }
+            '\'' | '"' => {
+                i += 1;
+                while let Some(d) = char_at(i) {
+                    i += 1;
+                    match d {
+                        '\\' if c == '"' => i += 1,
+                        d if d == c => break,
+                        _ => (),
+                    }
+                }
+                i = i.min(chars.len());
+                word_kind.get_or_insert(TokenKind::Argument);
+                expect_program = false;
+                TokenKind::String
+            }
+            '$' => {
+                i += 1;
+                match char_at(i) {
+                    Some(open @ ('{' | '(')) => {
+                        let close = if open == '{' { '}' } else { ')' };
+                        let mut depth = 0;
+                        while let Some(d) = char_at(i) {
+                            i += 1;
+                            if d == open {
+                                depth += 1;
+                            } else if d == close {
+                                depth -= 1;
+                                if depth == 0 {
+                                    break;
+                                }
+                            }
+                        }
+                    }
+                    Some(d) if d.is_ascii_digit() || "?#@*!$-".contains(d) => i += 1,
+                    _ => {
+                        while char_at(i).is_some_and(|d| d.is_ascii_alphanumeric() || d == '_') {
+                            i += 1;
+                        }
+                    }
+                }
+                word_kind.get_or_insert(TokenKind::Argument);
+                expect_program = false;
+                TokenKind::Variable
+            }
+            '<' if placeholder_at(&command[byte_at(i)..]).is_some() => {
+                let name = placeholder_at(&command[byte_at(i)..]).unwrap_or_default();
+                i += name.chars().count() + 2;
+                word_kind.get_or_insert(TokenKind::Argument);
+                expect_program = false;
+                TokenKind::Placeholder
+            }
+            '|' | '&' | ';' | '<' | '>' => {
+                if matches!(c, '|' | '&' | ';') {
+                    expect_program = true;
+                }
+                i += operator_len(&chars[i..]);
+                word_kind = None;
+                TokenKind::Operator
+            }
+            // File descriptor of a redirection, e.g. `2>`
+            c if c.is_ascii_digit() && word_kind.is_none() && fd_redirection(&chars[i..]) => {
+                while char_at(i).is_some_and(|d| d.is_ascii_digit()) {
+                    i += 1;
+                }
+                i += operator_len(&chars[i..]);
+                TokenKind::Operator
+            }
+            _ => {
+                while char_at(i).is_some_and(|d| !d.is_whitespace() && !"'\"$|&;<>".contains(d)) {
+                    i += 1;
+                }
+                let word = &command[byte_at(start)..byte_at(i)];
+                *word_kind.get_or_insert_with(|| {
+                    if expect_program && word.contains('=') && !word.starts_with('=') {
+                        TokenKind::Variable
+                    } else if expect_program {
+                        expect_program = false;
+                        TokenKind::Program
+                    } else if word.starts_with('-') {
+                        TokenKind::Flag
+                    } else {
+                        TokenKind::Argument
+                    }
+                })
+            }
+        };
+
+        tokens.push((kind, &command[byte_at(start)..byte_at(i)]));
+    }
+
+    tokens
+}
+
+/// Length in chars of the operator starting at `chars`, e.g. `|`, `&&`, `>>`, `>&2`
+fn operator_len(chars: &[(usize, char)]) -> usize {
+    let c = |i: usize| chars.get(i).map(|&(_, c)| c);
+    match (c(0), c(1)) {
+        (Some('|'), Some('|'))
+        | (Some('&'), Some('&'))
+        | (Some('>'), Some('>'))
+        | (Some(';'), Some(';')) => 2,
+        (Some('>' | '<'), Some('&')) => {
+            2 + chars[2..]
+                .iter()
+                .take_while(|&&(_, d)| d.is_ascii_digit() || d == '-')
+                .count()
+        }
+        _ => 1,
+    }
+}
+
+/// Whether `chars` starts with digits followed by a redirection, e.g. `2>`
+fn fd_redirection(chars: &[(usize, char)]) -> bool {
+    chars
+        .iter()
+        .find(|(_, d)| !d.is_ascii_digit())
+        .is_some_and(|&(_, d)| d == '>' || d == '<')
+}
+
+/// Style of each kind of token
+pub fn token_style(kind: TokenKind, theme: &Theme) -> Style {
+    match kind {
+        TokenKind::Program => Style::new().fg(theme.program).add_modifier(Modifier::BOLD),
+        TokenKind::Flag => Style::new().fg(theme.flag),
+        TokenKind::Argument => Style::new().fg(theme.argument),
+        TokenKind::String => Style::new().fg(theme.string),
+        TokenKind::Variable => Style::new().fg(theme.variable),
+        TokenKind::Operator => Style::new().fg(theme.operator).add_modifier(Modifier::BOLD),
+        TokenKind::Placeholder => Style::new()
+            .fg(theme.placeholder)
+            .add_modifier(Modifier::ITALIC),
+        TokenKind::Comment => Style::new().fg(theme.comment),
+        TokenKind::Whitespace => Style::new(),
+    }
+}
+
+/// Highlight a shell command
+pub fn highlight_command(command: &str, theme: &Theme) -> Line<'static> {
+    Line::from(
+        tokenize(command)
+            .into_iter()
+            .map(|(kind, text)| Span::styled(text.to_string(), token_style(kind, theme)))
+            .collect::<Vec<Span>>(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use TokenKind::*;
+
+    fn words(command: &str) -> Vec<(TokenKind, &str)> {
+        tokenize(command)
+            .into_iter()
+            .filter(|(kind, _)| *kind != Whitespace)
+            .collect()
+    }
+
+    #[test]
+    fn tokens_give_back_the_command() {
+        for command in [
+            "g++ -o main main.cpp -I<include-path> -L<lib-path> -std=c++17",
+            "echo \"a \\\" b\" 'c d' $(pwd) ${HOME} 2>&1 | tee < in > out",
+            "unterminated \"string",
+            "ünïcödé → <plâce>",
+        ] {
+            let joined: std::string::String = tokenize(command).iter().map(|(_, t)| *t).collect();
+            assert_eq!(joined, command);
+        }
+    }
+
+    #[test]
+    fn tokenize_programs_and_flags() {
+        assert_eq!(
+            words("g++ -o main -I<include-path> main.cpp"),
+            vec![
+                (Program, "g++"),
+                (Flag, "-o"),
+                (Argument, "main"),
+                (Flag, "-I"),
+                (Placeholder, "<include-path>"),
+                (Argument, "main.cpp"),
+            ]
+        );
+    }
+
+    #[test]
+    fn tokenize_pipes_and_redirections() {
+        assert_eq!(
+            words("cat < in.txt | grep -v x 2>&1 >> out && CC=gcc make; ls &"),
+            vec![
+                (Program, "cat"),
+                (Operator, "<"),
+                (Argument, "in.txt"),
+                (Operator, "|"),
+                (Program, "grep"),
+                (Flag, "-v"),
+                (Argument, "x"),
+                (Operator, "2>&1"),
+                (Operator, ">>"),
+                (Argument, "out"),
+                (Operator, "&&"),
+                (Variable, "CC=gcc"),
+                (Program, "make"),
+                (Operator, ";"),
+                (Program, "ls"),
+                (Operator, "&"),
+            ]
+        );
+    }
+
+    #[test]
+    fn tokenize_strings_variables_and_comments() {
+        assert_eq!(
+            words("git commit -m \"fix: $msg\" --author='Me' $USER ${X:-1} $(date +%F) # note"),
+            vec![
+                (Program, "git"),
+                (Argument, "commit"),
+                (Flag, "-m"),
+                (String, "\"fix: $msg\""),
+                (Flag, "--author="),
+                (String, "'Me'"),
+                (Variable, "$USER"),
+                (Variable, "${X:-1}"),
+                (Variable, "$(date +%F)"),
+                (Comment, "# note"),
+            ]
+        );
+    }
+
+    #[test]
+    fn highlight_with_styles() {
+        let theme = Theme::dark();
+        let line = highlight_command("ls <dir>", &theme);
+        assert_eq!(line.spans.len(), 3);
+        assert_eq!(line.spans[0].style, token_style(Program, &theme));
+        assert_eq!(line.spans[2].style, token_style(Placeholder, &theme));
+    }
+}
@@ -0,0 +1,256 @@
+//! Color themes of the terminal application, built-in or defined in the configuration
+
+use crate::config::Config;
+use color_eyre::eyre::eyre;
+use ratatui::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Name of the built-in themes
+pub const THEME_NAMES: [&str; 4] = ["dark", "light", "high-contrast", "no-color"];
+
+/// Colors used to render every screen. Custom themes in the configuration file
+/// only need to set the colors that differ from the dark theme
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Theme {
+    /// Default text of the lists
+    pub text: Color,
+    /// Title and keybindings bars
+    pub accent: Color,
+    /// Background of the selected item
+    pub selection: Color,
+    /// Short descriptions
+    pub info: Color,
+    /// Long description panel
+    pub description_fg: Color,
+    pub description_bg: Color,
+    /// Markdown elements of the long descriptions
+    pub heading: Color,
+    pub code: Color,
+    pub link: Color,
+    /// Pieces of the highlighted shell commands
+    pub program: Color,
+    pub flag: Color,
+    pub argument: Color,
+    pub string: Color,
+    pub variable: Color,
+    pub operator: Color,
+    pub placeholder: Color,
+    pub comment: Color,
+    /// Errors of the log panel
+    pub error: Color,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Theme::dark()
+    }
+}
+
+impl Theme {
+    pub fn dark() -> Theme {
+        Theme {
+            text: Color::White,
+            accent: Color::Yellow,
+            selection: Color::LightMagenta,
+            info: Color::Yellow,
+            description_fg: Color::White,
+            description_bg: Color::Black,
+            heading: Color::LightCyan,
+            code: Color::LightYellow,
+            link: Color::LightBlue,
+            program: Color::LightGreen,
+            flag: Color::LightCyan,
+            argument: Color::White,
+            string: Color::Yellow,
+            variable: Color::LightMagenta,
+            operator: Color::LightRed,
+            placeholder: Color::LightBlue,
+            comment: Color::DarkGray,
+            error: Color::LightRed,
+        }
+    }
+
+    pub fn light() -> Theme {
+        Theme {
+            text: Color::Black,
+            accent: Color::Blue,
+            selection: Color::LightCyan,
+            info: Color::Magenta,
+            description_fg: Color::Black,
+            description_bg: Color::White,
+            heading: Color::Blue,
+            code: Color::Red,
+            link: Color::Blue,
+            program: Color::Green,
+            flag: Color::Blue,
+            argument: Color::Black,
+            string: Color::Red,
+            variable: Color::Magenta,
+            operator: Color::Red,
+            placeholder: Color::Cyan,
+            comment: Color::Gray,
+            error: Color::Red,
+        }
+    }
+
+    pub fn high_contrast() -> Theme {
+        Theme {
+            text: Color::White,
+            accent: Color::LightYellow,
+            selection: Color::Blue,
+            info: Color::White,
+            description_fg: Color::White,
+            description_bg: Color::Black,
+            heading: Color::LightYellow,
+            code: Color::LightGreen,
+            link: Color::LightCyan,
+            program: Color::LightGreen,
+            flag: Color::LightCyan,
+            argument: Color::White,
+            string: Color::LightYellow,
+            variable: Color::LightMagenta,
+            operator: Color::LightRed,
+            placeholder: Color::LightCyan,
+            comment: Color::Gray,
+            error: Color::LightRed,
+        }
+    }
+
+    /// Terminal default colors everywhere, see <https://no-color.org>
+    pub fn no_color() -> Theme {
+        Theme {
+            text: Color::Reset,
+            accent: Color::Reset,
+            selection: Color::Reset,
+            info: Color::Reset,
+            description_fg: Color::Reset,
+            description_bg: Color::Reset,
+            heading: Color::Reset,
+            code: Color::Reset,
+            link: Color::Reset,
+            program: Color::Reset,
+            flag: Color::Reset,
+            argument: Color::Reset,
+            string: Color::Reset,
+            variable: Color::Reset,
+            operator: Color::Reset,
+            placeholder: Color::Reset,
+            comment: Color::Reset,
+            error: Color::Reset,
+        }
+    }
+
+    /// Built-in theme with the given name, if any
+    pub fn builtin(name: &str) -> Option<Theme> {
+        match name {
+            "dark" => Some(Theme::dark()),
+            "light" => Some(Theme::light()),
+            "high-contrast" => Some(Theme::high_contrast()),
+            "no-color" => Some(Theme::no_color()),
+            _ => None,
+        }
+    }
+
+    /// Theme selected in the configuration. Custom themes take precedence over the
+    /// built-in ones, and the `NO_COLOR` environment variable over both
+    pub fn from_config(config: &Config) -> color_eyre::Result<Theme> {
+        // Every custom theme is checked, even if another one is selected
+        let mut themes = HashMap::new();
+        for (name, colors) in &config.themes {
+            let theme: Theme = toml::Value::Table(colors.clone())
+                .try_into()
+                .map_err(|e| eyre!("invalid theme '{}': {}", name, e))?;
+            themes.insert(name.as_str(), theme);
+        }
+
+        if std::env::var("NO_COLOR").is_ok_and(|v| !v.is_empty()) {
+            return Ok(Theme::no_color());
+        }
+
+        themes
+            .remove(config.theme.as_str())
+            .or_else(|| Theme::builtin(&config.theme))
+            .ok_or_else(|| {
+                eyre!(
+                    "unknown theme '{}' (built-in themes: {})",
+                    config.theme,
+                    THEME_NAMES.join(", ")
+                )
+            })
+    }
+
+    /// Style of the selected item of a list. Without colors it is shown reversed
+    pub fn selection_style(&self) -> Style {
+        let style = Style::new().add_modifier(Modifier::BOLD).bg(self.selection);
+        match self.selection {
+            Color::Reset => style.add_modifier(Modifier::REVERSED),
+            _ => style,
+        }
+    }
+
+    /// Style of the borders of a panel, highlighted when it has the focus
+    pub fn border_style(&self, focused: bool) -> Style {
+        match focused {
+            true => Style::new().fg(self.accent),
+            false => Style::new(),
+        }
+    }
+
+    /// Style of the long description panel
+    pub fn description_style(&self) -> Style {
+        Style::new().fg(self.description_fg).bg(self.description_bg)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builtin_themes() {
+        for name in THEME_NAMES {
+            assert!(Theme::builtin(name).is_some());
+        }
+        assert!(Theme::builtin("unknown").is_none());
+        assert_eq!(Theme::default(), Theme::dark());
+    }
+
+    #[test]
+    fn no_color_selection_is_reversed() {
+        let style = Theme::no_color().selection_style();
+        assert!(style.add_modifier.contains(Modifier::REVERSED));
+
+        let style = Theme::dark().selection_style();
+        assert!(!style.add_modifier.contains(Modifier::REVERSED));
+    }
+
+    #[test]
+    fn custom_theme_from_config() {
+        let config: Config = toml::from_str(
+            r##"
+            theme = "mine"
+
+            [themes.mine]
+            selection = "#ff8700"
+            program = "light-red"
+            "##,
+        )
+        .unwrap();
+
+        let theme = Theme::from_config(&config).unwrap();
+        assert_eq!(theme.selection, Color::Rgb(0xff, 0x87, 0x00));
+        assert_eq!(theme.program, Color::LightRed);
+        assert_eq!(theme.text, Theme::dark().text);
+
+        let config: Config = toml::from_str("theme = \"light\"").unwrap();
+        assert_eq!(Theme::from_config(&config).unwrap(), Theme::light());
+
+        let config: Config = toml::from_str("theme = \"unknown\"").unwrap();
+        assert!(Theme::from_config(&config).is_err());
+
+        let config: Config = toml::from_str("[themes.mine]\nselection = \"nope\"").unwrap();
+        assert!(Theme::from_config(&config).is_err());
+    }
+}
@@ -1,5 +1,6 @@
-use crate::config::{PROJECT_AUTHOR, PROJECT_NAME, PROJECT_VERSION};
-use crate::model::Model;
+use super::theme::Theme;
+use crate::config::{BREADCRUMB_SEPARATOR_STRING, PROJECT_AUTHOR, PROJECT_NAME, PROJECT_VERSION};
+use crate::model::{CurrentScreen, Model};
 use ratatui::widgets::block::*;
 use ratatui::{prelude::*, widgets::*};
 
@@ -12,11 +13,15 @@ use ratatui::{prelude::*, widgets::*};
 //  ---------------------------
 // |                           |
 //  ---------------------------
-pub(crate) fn render_title(_: &Model, f: &mut Frame, area: Rect) {
-    // Make title block
-    let title_block = Block::default()
+pub(crate) fn render_title(model: &Model, theme: &Theme, f: &mut Frame, area: Rect) {
+    // Make title block, with the path of the selected group in the secondary screen
+    let mut title_block = Block::default()
         .borders(Borders::ALL)
         .style(Style::default());
+    if model.current_screen == CurrentScreen::Secondary {
+        let breadcrumb = model.breadcrumb().join(BREADCRUMB_SEPARATOR_STRING);
+        title_block = title_block.title(format!(" {} ", breadcrumb).fg(theme.info));
+    }
 
     // Fill text
     let title = Paragraph::new(Text::from(
@@ -25,7 +30,7 @@ pub(crate) fn render_title(_: &Model, f: &mut Frame, area: Rect) {
             PROJECT_NAME, PROJECT_VERSION, PROJECT_AUTHOR
         )
         .bold()
-        .yellow(),
+        .fg(theme.accent),
     ))
     .block(title_block)
     .centered();
@@ -1,19 +1,42 @@
 use crossterm::{
+    event::{DisableMouseCapture, EnableMouseCapture},
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
     ExecutableCommand,
 };
 use ratatui::prelude::*;
+use ratatui::{TerminalOptions, Viewport};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::{io::stdout, panic};
 
-pub fn init_terminal() -> color_eyre::Result<Terminal<impl Backend>> {
+/// Whether the terminal was taken over full-screen, to know what to restore
+static FULLSCREEN: AtomicBool = AtomicBool::new(false);
+
+/// Take over the whole terminal in the alternate screen or, with `inline`, only that many
+/// lines below the prompt so that the scrollback stays visible. The mouse is only
+/// captured full-screen, so that the wheel still scrolls back in inline mode
+pub fn init_terminal(inline: Option<u16>) -> color_eyre::Result<Terminal<impl Backend>> {
     enable_raw_mode()?;
-    stdout().execute(EnterAlternateScreen)?;
-    let terminal = Terminal::new(CrosstermBackend::new(stdout()))?;
+    let viewport = match inline {
+        Some(height) => Viewport::Inline(height),
+        None => {
+            stdout().execute(EnterAlternateScreen)?;
+            stdout().execute(EnableMouseCapture)?;
+            FULLSCREEN.store(true, Ordering::SeqCst);
+            Viewport::Fullscreen
+        }
+    };
+    let terminal = Terminal::with_options(
+        CrosstermBackend::new(stdout()),
+        TerminalOptions { viewport },
+    )?;
     Ok(terminal)
 }
 
 pub fn restore_terminal() -> color_eyre::Result<()> {
-    stdout().execute(LeaveAlternateScreen)?;
+    if FULLSCREEN.swap(false, Ordering::SeqCst) {
+        stdout().execute(DisableMouseCapture)?;
+        stdout().execute(LeaveAlternateScreen)?;
+    }
     disable_raw_mode()?;
     Ok(())
 }
@@ -25,6 +48,7 @@ pub fn install_panic_hook() -> color_eyre::Result<()> {
     // convert from a color_eyre PanicHook to a standard panic hook
     let panic_hook = panic_hook.into_panic_hook();
     panic::set_hook(Box::new(move |panic_info| {
+        tracing::error!("{}", panic_info);
         restore_terminal().unwrap();
         panic_hook(panic_info);
     }));
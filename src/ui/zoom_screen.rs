@@ -0,0 +1,87 @@
+use super::keybindings_block::render_keybindings;
+use super::secondary_screen::{long_info_paragraph, render_scrollbar, scroll_bounds};
+use super::shell::highlight_command;
+use super::theme::Theme;
+use super::title_block::render_title;
+use super::{panel_padding, screen_layout, Hit, Rendered};
+use crate::config::*;
+use crate::model::{Model, Panel, Scroll};
+use ratatui::widgets::block::*;
+use ratatui::{prelude::*, widgets::*};
+
+//  ----------------------------
+// |           TITLE            |
+//  ----------------------------
+// |          COMMAND           |
+// |         SHORT INFO         |
+// |         LONG INFO          |
+//  ----------------------------
+// |         INSTRUCTIONS       |
+//  ----------------------------
+pub fn render_zoom_screen(model: &Model, theme: &Theme, f: &mut Frame) -> Rendered {
+    let [title_area, entry_area, instruction_area] = screen_layout(model, f.size());
+
+    render_title(model, theme, f, title_area);
+    let long_info_scroll = render_entry(model, theme, f, entry_area);
+    render_keybindings(model, theme, f, instruction_area);
+
+    Rendered {
+        long_info_scroll: Some(long_info_scroll),
+        help_scroll: None,
+        hits: vec![(entry_area, Hit::Panel(Panel::Description))],
+    }
+}
+
+//  ----------------------------
+// |                            |
+//  ----------------------------
+// |                            |
+// |           THIS             |
+// |                            |
+//  ----------------------------
+// |                            |
+//  ----------------------------
+fn render_entry(model: &Model, theme: &Theme, f: &mut Frame, area: Rect) -> Scroll {
+    let entry = &model.entries[model.idx_entrygroup].entries[model.idx_entry];
+
+    let outer_block = Block::new()
+        .borders(Borders::ALL)
+        .title_alignment(Alignment::Left)
+        .padding(panel_padding(model, f.size(), 2, 2))
+        .title(ZOOM_SCREEN_TITLE);
+    let outer_area = area;
+    let inner_area = outer_block.inner(outer_area);
+
+    // The whole command is always shown, wrapped if needed
+    let header = Paragraph::new(vec![
+        highlight_command(&model.shown(entry, &entry.command), theme),
+        Line::default(),
+        Line::from(model.shown(entry, &entry.short_info).into_owned())
+            .italic()
+            .fg(theme.info),
+    ])
+    .wrap(Wrap { trim: false });
+    let header_height = header.line_count(inner_area.width) as u16 + 1;
+
+    let [header_area, long_info_area] = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(header_height), Constraint::Min(1)])
+        .areas(inner_area);
+
+    // The long description scrolls below it
+    let paragraph = long_info_paragraph(
+        model,
+        theme,
+        model.shown(entry, &entry.long_info).into_owned(),
+    );
+    let scroll = scroll_bounds(&paragraph, long_info_area, model.long_info_scroll);
+    let paragraph = paragraph.scroll((scroll.offset, 0));
+
+    // Render
+    f.render_widget(outer_block, outer_area);
+    f.render_widget(header, header_area);
+    f.render_widget(paragraph, long_info_area);
+    render_scrollbar(f, outer_area, scroll);
+
+    scroll
+}